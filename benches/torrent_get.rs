@@ -0,0 +1,150 @@
+//! Benchmark of `torrent-get` response deserialization.
+//! Compare the time and the peak memory of the full `Response` with the streaming `TorrentVisitor` path.
+//!
+//! Run with `cargo bench --bench torrent_get`
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use prosa_ovserver::transmission::api::{Response, TorrentsArguments};
+
+/// Allocator that keep track of the peak of allocated memory
+struct PeakAlloc {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = self.current.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            self.peak.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        self.current.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+const ITERATIONS: u32 = 10;
+
+/// Generate a `torrent-get` response with files and peers for every torrent
+fn torrent_get_response(torrent_count: usize) -> String {
+    let torrents: Vec<serde_json::Value> = (0..torrent_count)
+        .map(|id| {
+            let files: Vec<serde_json::Value> = (0..20)
+                .map(|f| {
+                    serde_json::json!({
+                        "bytesCompleted": 1048576,
+                        "length": 2097152,
+                        "name": format!("Torrent {id}/file {f}.mkv"),
+                        "begin_piece": f * 8,
+                        "end_piece": f * 8 + 8,
+                    })
+                })
+                .collect();
+            let peers: Vec<serde_json::Value> = (0..10)
+                .map(|p| {
+                    serde_json::json!({
+                        "address": format!("10.0.{}.{p}", id % 256),
+                        "bytes_to_client": 0,
+                        "bytes_to_peer": 4096,
+                        "clientName": "Transmission 4.0.6",
+                        "clientIsChoked": false,
+                        "clientIsInterested": true,
+                        "flagStr": "TDEI",
+                        "isDownloadingFrom": false,
+                        "isEncrypted": true,
+                        "isIncoming": false,
+                        "isUploadingTo": true,
+                        "isUTP": true,
+                        "peerIsChoked": false,
+                        "peerIsInterested": true,
+                        "peer_id": "-TR4060-000000000000",
+                        "port": 51413,
+                        "progress": 0.5,
+                        "rateToClient": 0,
+                        "rateToPeer": 1024,
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "id": id,
+                "name": format!("Torrent {id}"),
+                "hashString": format!("{id:040x}"),
+                "status": 6,
+                "percentDone": 1.0,
+                "files": files,
+                "peers": peers,
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "arguments": { "torrents": torrents },
+        "result": "success",
+    })
+    .to_string()
+}
+
+/// Run a deserialization method, and return its average duration with the peak of memory allocated during it
+fn bench<F>(mut f: F) -> (Duration, usize)
+where
+    F: FnMut() -> usize,
+{
+    let base = ALLOC.current.load(Ordering::Relaxed);
+    ALLOC.peak.store(base, Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    (elapsed, ALLOC.peak.load(Ordering::Relaxed) - base)
+}
+
+fn main() {
+    for torrent_count in [100, 1000, 5000] {
+        let body = torrent_get_response(torrent_count);
+
+        let (full_time, full_peak) = bench(|| {
+            let response: Response = serde_json::from_reader(body.as_bytes()).unwrap();
+            response.arguments.torrents.len()
+        });
+        let (visit_time, visit_peak) = bench(|| {
+            let mut count = 0;
+            Response::from_reader_visit(body.as_bytes(), &mut |torrent: TorrentsArguments| {
+                black_box(torrent);
+                count += 1;
+            })
+            .unwrap();
+            count
+        });
+
+        println!(
+            "torrent-get {torrent_count} torrents ({} KiB):",
+            body.len() / 1024
+        );
+        println!(
+            "    full response: {full_time:>12?} - peak {:>8} KiB",
+            full_peak / 1024
+        );
+        println!(
+            "    visitor:       {visit_time:>12?} - peak {:>8} KiB",
+            visit_peak / 1024
+        );
+    }
+}
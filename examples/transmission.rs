@@ -18,6 +18,7 @@ async fn get_torrent(
             TorrentField::AddedDate,
         ],
         None,
+        None,
     );
     let torrent_get_json = serde_json::to_string(&torrent_get).unwrap();
     println!("Try to send: {torrent_get_json}");
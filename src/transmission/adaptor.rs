@@ -1,7 +1,11 @@
-use std::{collections::HashMap, convert::Infallible};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    convert::Infallible,
+    path::PathBuf,
+};
 
 use bytes::{Buf as _, Bytes};
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, SubsecRound as _, Utc};
 use http::{Method, Request, Response, StatusCode};
 use http_body_util::{BodyExt as _, Full, combinators::BoxBody};
 use hyper::body::Incoming;
@@ -20,32 +24,86 @@ use tracing::{debug, info, warn};
 
 use crate::transmission::{
     self,
-    api::{SessionStats, Status, TorrentField, TorrentSetParams},
+    api::{
+        Id, RpcDialect, SessionStats, Status, TorrentField, TorrentGetFormat, TorrentId,
+        TorrentSetParams, TorrentsArguments,
+    },
+    ban::{PeerBan, PeerBanSettings},
+    content::TorrentContent,
+    cross_seed::{CrossSeedIndex, CrossSeedSettings},
+    deletion::DeletionSettings,
+    event::{TorrentEvent, TorrentEventDetector},
+    feed::{FeedSettings, Feeds},
+    history::{HistorySettings, HistoryStore, Transfer},
+    notify::{NotifierSettings, Notifiers},
+    obligation::{ObligationState, SeedObligation},
+    optimizer::{SeedCandidate, SeedOptimizerSettings},
+    rules::{FileRules, FileRulesSettings},
+    watch::{WatchFolderSettings, WatchFolders},
 };
 
+/// Fields always requested on `torrent-get`
+const TORRENT_FIELDS: [TorrentField; 6] = [
+    TorrentField::Id,
+    TorrentField::Name,
+    TorrentField::Status,
+    TorrentField::TrackerList,
+    TorrentField::AddedDate,
+    TorrentField::UploadLimited,
+];
+
+/// Maximum number of torrent events waiting to be sent
+const MAX_PENDING_EVENTS: usize = 1024;
+
+/// Default path of the Transmission RPC
+const DEFAULT_RPC_PATH: &str = "/transmission/rpc";
+
+/// Name of the daemon when no daemon list is set
+const DEFAULT_INSTANCE: &str = "default";
+
 #[derive(Default, Debug)]
 pub enum TorrentFetchState {
+    /// Get the RPC version to negotiate the dialect
+    SessionGet,
     #[default]
     SessionStats,
     TorrentGet,
+    /// Get only recently active torrents, and removed ones
+    TorrentGetRecentlyActive,
+    /// Get files of torrents to apply file rules, with the pending mutations
+    TorrentFiles(Vec<Id>, Vec<transmission::api::Method>),
     TorrentMut(Vec<transmission::api::Method>),
     End,
 }
 
 impl TorrentFetchState {
     /// Getter of the method to send
-    pub fn get_method(&self) -> Option<transmission::api::Method> {
+    pub fn get_method(&self, torrent_fields: &[TorrentField]) -> Option<transmission::api::Method> {
         match self {
+            TorrentFetchState::SessionGet => Some(transmission::api::Method::SessionGet),
             TorrentFetchState::SessionStats => Some(transmission::api::Method::SessionStats),
             TorrentFetchState::TorrentGet => Some(transmission::api::Method::TorrentGet(
+                torrent_fields.to_vec(),
+                None,
+                None,
+            )),
+            TorrentFetchState::TorrentGetRecentlyActive => {
+                Some(transmission::api::Method::TorrentGet(
+                    torrent_fields.to_vec(),
+                    Some(vec![TorrentId::RecentlyActive]),
+                    None,
+                ))
+            }
+            TorrentFetchState::TorrentFiles(ids, _) => Some(transmission::api::Method::TorrentGet(
                 vec![
                     TorrentField::Id,
+                    TorrentField::HashString,
                     TorrentField::Name,
-                    TorrentField::Status,
-                    TorrentField::TrackerList,
-                    TorrentField::AddedDate,
-                    TorrentField::UploadLimited,
+                    TorrentField::Files,
+                    TorrentField::FileStats,
+                    TorrentField::PrimaryMimeType,
                 ],
+                Some(ids.iter().cloned().map(TorrentId::from).collect()),
                 None,
             )),
             TorrentFetchState::TorrentMut(methods) => methods.first().cloned(),
@@ -64,14 +122,156 @@ struct TorrentSettings {
     remove_after: Option<u32>,
     /// Remove public torrent after an amount of hours
     remove_public_after: Option<u64>,
+    /// Policy to ban leechers and fake clients
+    peer_ban: Option<PeerBanSettings>,
+    /// Rules applied to files of newly added torrents
+    file_rules: Option<FileRulesSettings>,
+    /// Name of the ProSA service to which torrent lifecycle events are sent.
+    /// Each event is sent point to point to a single processor of the service, so it has to dispatch them to several consumers
+    event_service: Option<String>,
+    /// Enable incremental polling of recently active torrents, with a full resync every amount of minutes
+    incremental_resync: Option<u64>,
+    /// Request torrents with the table format to lower the payload size
+    #[serde(default)]
+    table_format: bool,
+    /// Force the RPC dialect instead of negotiating it from the `rpc-version` of `session-get`
+    rpc_dialect: Option<RpcDialect>,
+    /// Policy to delete local data of removed torrents, only when no other torrent seed the same content
+    delete_local_data: Option<DeletionSettings>,
+    /// Detection of the same content seeded by several torrents, to keep their local data
+    #[serde(default)]
+    cross_seed: CrossSeedSettings,
+    /// Torrents with one of these labels are pinned, and excluded from every automated mutation
+    #[serde(default)]
+    pinned_labels: Vec<String>,
+    /// Hash of pinned torrents, excluded from every automated mutation
+    #[serde(default)]
+    pinned_hashes: Vec<String>,
+    /// Seeding obligations of private trackers. Torrents that still owe seed time or ratio are not removed
+    #[serde(default)]
+    seed_obligations: Vec<SeedObligation>,
+    /// Optimizer that start the most useful torrents to seed, and stop the others
+    seed_optimizer: Option<SeedOptimizerSettings>,
+    /// Folders scanned on every fetch to add their `.torrent` and magnet files
+    #[serde(default)]
+    watch_folders: Vec<WatchFolderSettings>,
+    /// RSS and Atom feeds polled to add their matching items
+    #[serde(default)]
+    feeds: Vec<FeedSettings>,
+    /// Path of the JSON file where the items seen on feeds are remembered
+    feed_state_path: Option<PathBuf>,
+    /// Persistent history of torrents (first seen, completion, peak ratio, mutations, transfer by tracker and label)
+    history: Option<HistorySettings>,
+    /// Sinks notified of torrent events
+    #[serde(default)]
+    notifiers: Vec<NotifierSettings>,
+}
+
+/// Settings of a Transmission daemon
+#[derive(Debug, Deserialize)]
+struct DaemonSettings {
+    /// Name of the daemon, exported as `instance` attribute of metrics
+    name: String,
+    /// Path of the daemon RPC on the fetcher target (ex: behind a reverse proxy).
+    /// The fetcher keep a single connection to its target, so daemons can't have their own host or port:
+    /// instances listening on separate ports must be exposed on one target by a reverse proxy, with a path per instance
+    #[serde(default = "DaemonSettings::default_rpc_path")]
+    rpc_path: String,
+    #[serde(flatten)]
+    settings: TorrentSettings,
+}
+
+impl DaemonSettings {
+    fn default_rpc_path() -> String {
+        DEFAULT_RPC_PATH.to_string()
+    }
+}
+
+/// Settings of the adaptor
+#[derive(Debug, Deserialize)]
+struct TorrentAdaptorSettings {
+    /// Settings of the daemon when no daemon list is set
+    #[serde(flatten)]
+    settings: TorrentSettings,
+    /// List of Transmission daemons to fetch, all of them through the fetcher target with their own RPC path
+    #[serde(default)]
+    daemons: Vec<DaemonSettings>,
 }
 
 impl TorrentSettings {
+    /// Getter of the fields to request on `torrent-get`
+    fn torrent_fields(&self) -> Vec<TorrentField> {
+        let mut torrent_fields = TORRENT_FIELDS.to_vec();
+        let mut add_fields = |fields: &[TorrentField]| {
+            for field in fields {
+                if !torrent_fields.contains(field) {
+                    torrent_fields.push(field.clone());
+                }
+            }
+        };
+
+        if self.peer_ban.is_some() {
+            add_fields(&[TorrentField::HashString, TorrentField::Peers]);
+        }
+
+        if self.file_rules.is_some() {
+            add_fields(&[
+                TorrentField::HashString,
+                TorrentField::MetadataPercentComplete,
+            ]);
+        }
+
+        if self.event_service.is_some() || !self.notifiers.is_empty() {
+            add_fields(&TorrentEventDetector::FIELDS);
+        }
+
+        if !self.pinned_labels.is_empty() {
+            add_fields(&[TorrentField::Labels]);
+        }
+
+        if !self.pinned_hashes.is_empty() {
+            add_fields(&[TorrentField::HashString]);
+        }
+
+        if !self.seed_obligations.is_empty() {
+            add_fields(&SeedObligation::FIELDS);
+        }
+
+        if self.seed_optimizer.is_some() {
+            add_fields(&SeedOptimizerSettings::FIELDS);
+        }
+
+        if self.delete_local_data.is_some() {
+            add_fields(&DeletionSettings::FIELDS);
+            add_fields(&self.cross_seed.fields());
+        }
+
+        if self.history.is_some() {
+            add_fields(&HistoryStore::FIELDS);
+        }
+
+        torrent_fields
+    }
+
+    /// Method to know if the torrent is pinned by its label or its hash
+    /// return `true` if it's the case
+    fn pinned(&self, torrent: &TorrentsArguments) -> bool {
+        torrent
+            .labels
+            .as_ref()
+            .is_some_and(|labels| labels.iter().any(|l| self.pinned_labels.contains(l)))
+            || torrent.hash_string.as_ref().is_some_and(|hash| {
+                self.pinned_hashes
+                    .iter()
+                    .any(|h| h.eq_ignore_ascii_case(hash))
+            })
+    }
+
     /// Method to check if the tracker is allowed
     /// return `true` if it's the case
-    fn tracker_allowed(&self, tracker: String) -> bool {
+    fn tracker_allowed(&self, tracker: &str) -> bool {
         for tracker_allow in &self.tracker_allowlist {
-            if tracker.contains(tracker_allow) {
+            if tracker.contains(tracker_allow.as_str()) {
                 return true;
             }
         }
@@ -101,14 +301,772 @@ impl TorrentSettings {
     }
 }
 
-#[derive(Adaptor)]
-pub struct TorrentAdaptor {
+/// State of a Transmission daemon fetched by the adaptor
+struct TransmissionDaemon {
+    /// Name of the daemon, exported as `instance` attribute of metrics
+    name: String,
     transmission_uri: hyper::Uri,
     settings: Option<TorrentSettings>,
+    torrent_fields: Vec<TorrentField>,
     state: TorrentFetchState,
+    peer_ban: PeerBan,
+    file_rules: Option<FileRules>,
+    /// Hash of torrents on which file rules were successfully applied
+    file_rules_applied: HashSet<String>,
+    event_detector: TorrentEventDetector,
+    /// Torrent table kept between fetches for incremental polling
+    torrents: HashMap<Id, TorrentsArguments>,
+    /// Files of watch folders waiting for their `torrent-add` result
+    watch_folders: WatchFolders,
+    /// Feeds state, with the items waiting for their `torrent-add` result
+    feeds: Feeds,
+    /// Persistent history of torrents, if enabled
+    history: Option<HistoryStore>,
+    /// Notification sinks state, for their rate limit
+    notifiers: Notifiers,
+    /// Date of the last full `torrent-get`
+    last_full_sync: Option<DateTime<Utc>>,
     session_id: Option<String>,
-    torrent_count: watch::Sender<Vec<Status>>,
-    torrent_stats: watch::Sender<SessionStats>,
+    /// Dialect of the RPC, negotiated with Transmission if not forced
+    rpc_dialect: Option<RpcDialect>,
+    /// Id of the next request
+    request_id: u64,
+}
+
+impl TransmissionDaemon {
+    fn new(
+        name: String,
+        rpc_path: &str,
+        settings: Option<TorrentSettings>,
+    ) -> Result<Self, String> {
+        let file_rules = settings
+            .as_ref()
+            .and_then(|s| s.file_rules.as_ref())
+            .map(FileRules::new)
+            .transpose()
+            .map_err(|e| format!("Wrong file rules pattern for `{name}`: {e}"))?;
+        for obligation in settings.iter().flat_map(|s| &s.seed_obligations) {
+            obligation
+                .check()
+                .map_err(|e| format!("Wrong seed obligation for `{name}`: {e}"))?;
+        }
+        let feeds = settings
+            .as_ref()
+            .map(|s| Feeds::new(&s.feeds, s.feed_state_path.clone()))
+            .transpose()?
+            .unwrap_or_default();
+        let history = settings
+            .as_ref()
+            .and_then(|s| s.history.as_ref())
+            .map(HistoryStore::open);
+        let notifiers = settings
+            .as_ref()
+            .map(|s| Notifiers::new(&s.notifiers))
+            .unwrap_or_default();
+        Ok(TransmissionDaemon {
+            transmission_uri: rpc_path
+                .parse::<hyper::Uri>()
+                .map_err(|e| format!("can't parse hyper::uri `{rpc_path}` : {e}"))?,
+            torrent_fields: settings
+                .as_ref()
+                .map(|s| s.torrent_fields())
+                .unwrap_or_else(|| TORRENT_FIELDS.to_vec()),
+            rpc_dialect: settings.as_ref().and_then(|s| s.rpc_dialect),
+            name,
+            settings,
+            state: TorrentFetchState::default(),
+            peer_ban: PeerBan::default(),
+            file_rules,
+            file_rules_applied: HashSet::new(),
+            event_detector: TorrentEventDetector::default(),
+            torrents: HashMap::new(),
+            watch_folders: WatchFolders::default(),
+            last_full_sync: None,
+            session_id: history
+                .as_ref()
+                .and_then(|h| h.session_id())
+                .map(String::from),
+            feeds,
+            history,
+            notifiers,
+            request_id: 1,
+        })
+    }
+
+    /// Getter of the first state of a fetch, to negotiate the dialect if needed
+    fn first_state(&self) -> TorrentFetchState {
+        if self.rpc_dialect.is_none() {
+            TorrentFetchState::SessionGet
+        } else {
+            TorrentFetchState::default()
+        }
+    }
+
+    /// Process torrents files retrieved from `torrent-get` to apply file rules.
+    /// Return the mutations to apply, that target torrents by hash to be recorded once successful
+    fn process_torrent_files(
+        &mut self,
+        torrents: Vec<TorrentsArguments>,
+    ) -> Vec<transmission::api::Method> {
+        let mut torrent_mut_list = Vec::new();
+        if let Some(file_rules) = &self.file_rules {
+            for torrent in torrents {
+                let (Some(hash), Some(content)) = (
+                    torrent.hash_string.clone(),
+                    TorrentContent::from_torrent(&torrent),
+                ) else {
+                    continue;
+                };
+
+                match file_rules.apply(&content, torrent.primary_mime_type.as_deref()) {
+                    Some(mut params) => {
+                        debug!("Apply file rules on torrent {:?}: {params:?}", torrent.name);
+                        params.ids = Some(vec![TorrentId::from(Id::Hash(hash))]);
+                        torrent_mut_list
+                            .push(transmission::api::Method::TorrentSet(Box::new(params)));
+                    }
+                    None => {
+                        self.file_rules_applied.insert(hash);
+                    }
+                }
+            }
+        }
+
+        torrent_mut_list
+    }
+
+    /// Getter of the incremental polling resync period, if enabled
+    fn incremental_resync(&self) -> Option<Duration> {
+        self.settings
+            .as_ref()
+            .and_then(|s| s.incremental_resync)
+            .map(|m| Duration::minutes(m as i64))
+    }
+
+    /// Method to know if a full `torrent-get` is needed, or if only recently active torrents can be retrieved
+    fn need_full_sync(&self) -> bool {
+        match (self.incremental_resync(), self.last_full_sync) {
+            (Some(resync), Some(last_full_sync)) => Utc::now() - last_full_sync >= resync,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Adaptor)]
+pub struct TorrentAdaptor {
+    /// Transmission daemons, fetched one after the other
+    daemons: Vec<TransmissionDaemon>,
+    /// Index of the daemon being fetched
+    current: usize,
+    /// Start date of the adaptor, torrents added after it are considered new
+    started: DateTime<Utc>,
+    /// Torrent events waiting to be sent, with their event service
+    events: VecDeque<(String, TorrentEvent)>,
+    torrent_count: watch::Sender<HashMap<String, Vec<Status>>>,
+    torrent_stats: watch::Sender<HashMap<String, SessionStats>>,
+    pinned_count: watch::Sender<HashMap<String, u64>>,
+    obligations_at_risk: watch::Sender<HashMap<String, u64>>,
+    tracker_transfer: watch::Sender<HashMap<String, BTreeMap<String, Transfer>>>,
+}
+
+impl TorrentAdaptor {
+    /// Process the torrent table built from `torrent-get` responses.
+    /// Return the mutations to apply, and the torrents that need their files to be inspected
+    async fn process_torrents(
+        &mut self,
+        torrents: &mut HashMap<Id, TorrentsArguments>,
+    ) -> (Vec<transmission::api::Method>, Vec<Id>) {
+        let TorrentAdaptor {
+            daemons,
+            current,
+            started,
+            events,
+            torrent_count,
+            pinned_count,
+            obligations_at_risk,
+            tracker_transfer,
+            ..
+        } = self;
+        let daemon = &mut daemons[*current];
+        let now = Utc::now();
+        let mut torrent_no_peer_list = Vec::new();
+        let mut torrent_rm_list = Vec::new();
+        let mut torrent_banned_list = Vec::new();
+        let mut torrent_files_list = Vec::new();
+        let mut torrents_status = Vec::with_capacity(torrents.len());
+
+        if let Some(history) = &mut daemon.history {
+            history.set_session_id(daemon.session_id.clone());
+            history.update(torrents.iter(), now);
+            tracker_transfer.send_modify(|tracker_transfer| {
+                tracker_transfer.insert(daemon.name.clone(), history.tracker_transfer().clone());
+            });
+            if let Err(e) = history.save().await {
+                warn!(instance = daemon.name, "Can't write the history: {e}");
+            }
+        }
+
+        if let Some(torrent_settings) = &daemon.settings
+            && (torrent_settings.event_service.is_some() || !torrent_settings.notifiers.is_empty())
+        {
+            let torrent_events = daemon.event_detector.update(torrents.values());
+            if !daemon.notifiers.is_empty() {
+                daemon.notifiers.notify(&daemon.name, &torrent_events);
+            }
+
+            if let Some(event_service) = &torrent_settings.event_service {
+                events.extend(
+                    torrent_events
+                        .into_iter()
+                        .map(|event| (event_service.clone(), event)),
+                );
+                if events.len() > MAX_PENDING_EVENTS {
+                    warn!(
+                        instance = daemon.name,
+                        "Too many pending torrent events, drop {} of them",
+                        events.len() - MAX_PENDING_EVENTS
+                    );
+                    events.drain(..events.len() - MAX_PENDING_EVENTS);
+                }
+            }
+        }
+
+        let mut seed_candidates = Vec::new();
+        let mut torrent_pinned_count = 0;
+        let mut torrent_at_risk_count = 0;
+        for (id, torrent) in torrents.iter() {
+            if let Some(status) = torrent.status {
+                torrents_status.push(status);
+            }
+
+            if let Some(torrent_settings) = &daemon.settings {
+                if torrent_settings.pinned(torrent) {
+                    torrent_pinned_count += 1;
+                    continue;
+                }
+
+                if let Some(peer_ban_settings) = &torrent_settings.peer_ban
+                    && let Some(hash) = &torrent.hash_string
+                    && let Some(peers) = &torrent.peers
+                    && daemon.peer_ban.inspect(peer_ban_settings, hash, peers, now)
+                {
+                    torrent_banned_list.push(id.clone());
+                }
+
+                if daemon.file_rules.is_some()
+                    && let Some(hash) = &torrent.hash_string
+                    && torrent.added_date.is_some_and(|d| d >= *started)
+                    && torrent.metadata_percent_complete.is_some_and(|p| p >= 1.0)
+                    && !daemon.file_rules_applied.contains(hash)
+                {
+                    torrent_files_list.push(id.clone());
+                }
+
+                let obligation_state =
+                    ObligationState::from_torrent(&torrent_settings.seed_obligations, torrent, now);
+                if obligation_state == ObligationState::AtRisk {
+                    torrent_at_risk_count += 1;
+                }
+
+                if !torrent.upload_limited
+                    && torrent
+                        .tracker_list
+                        .as_ref()
+                        .is_some_and(|t| !torrent_settings.tracker_allowed(t))
+                {
+                    torrent_no_peer_list.push(id.clone());
+                } else if obligation_state == ObligationState::Free
+                    && torrent
+                        .added_date
+                        .filter(|d| d.timestamp() > 0)
+                        .is_some_and(|d| torrent_settings.need_removal(d, torrent.is_private))
+                {
+                    torrent_rm_list.push(id.clone());
+                } else if torrent_settings.seed_optimizer.is_some()
+                    && torrent.percent_done.is_some_and(|p| p >= 1.0)
+                {
+                    seed_candidates.push(SeedCandidate::from_torrent(
+                        id.clone(),
+                        torrent,
+                        obligation_state != ObligationState::Free,
+                    ));
+                }
+            }
+        }
+
+        // Update the torrent table with the pending mutations to not apply them again on incremental polling
+        for id in &torrent_no_peer_list {
+            if let Some(torrent) = torrents.get_mut(id) {
+                torrent.upload_limited = true;
+            }
+        }
+
+        // Forget torrents that are not in Transmission anymore
+        if !daemon.file_rules_applied.is_empty() {
+            let hashes: HashSet<&String> = torrents
+                .values()
+                .filter_map(|t| t.hash_string.as_ref())
+                .collect();
+            daemon
+                .file_rules_applied
+                .retain(|hash| hashes.contains(hash));
+        }
+
+        // Delete local data only for torrents allowed by the policy, and the last ones that reference the content
+        let (torrent_rm_data_list, torrent_rm_list) = if !torrent_rm_list.is_empty()
+            && let Some(deletion_settings) = daemon
+                .settings
+                .as_ref()
+                .and_then(|s| s.delete_local_data.as_ref())
+        {
+            let (torrent_rm_data_list, mut torrent_rm_list): (Vec<Id>, Vec<Id>) = torrent_rm_list
+                .into_iter()
+                .partition(|id| torrents.get(id).is_some_and(|t| deletion_settings.allow(t)));
+            let (torrent_rm_data_list, torrent_seeded_list) =
+                CrossSeedIndex::new(torrents.iter()).split_removal(torrent_rm_data_list);
+            if !torrent_seeded_list.is_empty() {
+                info!(
+                    instance = daemon.name,
+                    "Keep local data of {} removed torrents still seeded by other torrents",
+                    torrent_seeded_list.len()
+                );
+                torrent_rm_list.extend(torrent_seeded_list);
+            }
+
+            if !torrent_rm_data_list.is_empty() {
+                let freed_bytes: u64 = torrent_rm_data_list
+                    .iter()
+                    .filter_map(|id| torrents.get(id).and_then(|t| t.size_when_done))
+                    .sum();
+                info!(
+                    instance = daemon.name,
+                    "Delete local data of {} removed torrents, {freed_bytes} bytes freed",
+                    torrent_rm_data_list.len()
+                );
+            }
+            (torrent_rm_data_list, torrent_rm_list)
+        } else {
+            (Vec::new(), torrent_rm_list)
+        };
+        for id in torrent_rm_list.iter().chain(&torrent_rm_data_list) {
+            torrents.remove(id);
+        }
+
+        torrent_count.send_modify(|torrent_count| {
+            torrent_count.insert(daemon.name.clone(), torrents_status);
+        });
+        pinned_count.send_modify(|pinned_count| {
+            pinned_count.insert(daemon.name.clone(), torrent_pinned_count);
+        });
+        obligations_at_risk.send_modify(|obligations_at_risk| {
+            obligations_at_risk.insert(daemon.name.clone(), torrent_at_risk_count);
+        });
+
+        let mut torrent_mut_list = Vec::new();
+        if let Some(peer_ban_settings) = daemon.settings.as_ref().and_then(|s| s.peer_ban.as_ref())
+        {
+            daemon.peer_ban.finish_cycle(peer_ban_settings, now);
+            if let Err(e) = daemon.peer_ban.write_blocklist(peer_ban_settings).await {
+                warn!(
+                    instance = daemon.name,
+                    "Can't write the peer blocklist: {e}"
+                );
+            }
+
+            if !torrent_banned_list.is_empty() {
+                info!(
+                    instance = daemon.name,
+                    "{} torrents hit by banned peers ({} banned)",
+                    torrent_banned_list.len(),
+                    daemon.peer_ban.banned().len()
+                );
+
+                if let Some(peer_limit) = peer_ban_settings.peer_limit {
+                    torrent_mut_list.push(transmission::api::Method::TorrentSet(Box::new(
+                        TorrentSetParams {
+                            ids: Some(
+                                torrent_banned_list
+                                    .iter()
+                                    .cloned()
+                                    .map(TorrentId::from)
+                                    .collect(),
+                            ),
+                            peer_limit: Some(peer_limit),
+                            ..Default::default()
+                        },
+                    )));
+                }
+
+                if peer_ban_settings.reannounce {
+                    torrent_mut_list.push(transmission::api::Method::TorrentReannounce(Some(
+                        TorrentId::List(torrent_banned_list),
+                    )));
+                }
+            }
+        }
+
+        if let Some(seed_optimizer) = daemon
+            .settings
+            .as_ref()
+            .and_then(|s| s.seed_optimizer.as_ref())
+        {
+            let (torrent_start_list, torrent_stop_list) = seed_optimizer.optimize(seed_candidates);
+            if !torrent_start_list.is_empty() || !torrent_stop_list.is_empty() {
+                info!(
+                    instance = daemon.name,
+                    "Seed optimizer start {} torrents and stop {} torrents",
+                    torrent_start_list.len(),
+                    torrent_stop_list.len()
+                );
+            }
+
+            if !torrent_start_list.is_empty() {
+                torrent_mut_list.push(transmission::api::Method::TorrentStart(Some(
+                    TorrentId::List(torrent_start_list),
+                )));
+            }
+
+            if !torrent_stop_list.is_empty() {
+                torrent_mut_list.push(transmission::api::Method::TorrentStop(Some(
+                    TorrentId::List(torrent_stop_list),
+                )));
+            }
+        }
+
+        if !torrent_no_peer_list.is_empty() {
+            torrent_mut_list.push(transmission::api::Method::TorrentSet(Box::new(
+                TorrentSetParams {
+                    ids: Some(
+                        torrent_no_peer_list
+                            .into_iter()
+                            .map(TorrentId::from)
+                            .collect(),
+                    ),
+                    peer_limit: Some(0),
+                    upload_limit: Some(0),
+                    upload_limited: Some(true),
+                    ..Default::default()
+                },
+            )));
+        }
+
+        if !torrent_rm_list.is_empty() {
+            torrent_mut_list.push(transmission::api::Method::TorrentRemove(
+                torrent_rm_list.into_iter().map(TorrentId::from).collect(),
+                false,
+            ));
+        }
+
+        if !torrent_rm_data_list.is_empty() {
+            torrent_mut_list.push(transmission::api::Method::TorrentRemove(
+                torrent_rm_data_list
+                    .into_iter()
+                    .map(TorrentId::from)
+                    .collect(),
+                true,
+            ));
+        }
+
+        // Torrents of watch folders are added before the ones of feeds, to route the `torrent-add` results
+        if let Some(torrent_settings) = &daemon.settings {
+            if !torrent_settings.watch_folders.is_empty() {
+                torrent_mut_list.extend(
+                    daemon
+                        .watch_folders
+                        .scan(&torrent_settings.watch_folders)
+                        .await,
+                );
+            }
+
+            if !torrent_settings.feeds.is_empty() {
+                torrent_mut_list.extend(daemon.feeds.poll(&torrent_settings.feeds).await);
+            }
+        }
+
+        (torrent_mut_list, torrent_files_list)
+    }
+
+    /// Getter of the daemon being fetched
+    fn daemon(&mut self) -> &mut TransmissionDaemon {
+        &mut self.daemons[self.current]
+    }
+
+    /// Process the HTTP response of the daemon being fetched
+    async fn process_daemon_response<M>(
+        &mut self,
+        response: Response<Incoming>,
+    ) -> Result<FetchAction<M>, FetcherError<M>>
+    where
+        M: std::marker::Send + prosa::core::msg::Tvf + std::default::Default,
+    {
+        let daemon = self.daemon();
+        if daemon.session_id.is_none() {
+            match response.status() {
+                StatusCode::OK | StatusCode::CONFLICT => {
+                    if let Some(session_id) = response.headers().get("x-transmission-session-id") {
+                        daemon.session_id = session_id.to_str().map(|s| s.to_string()).ok();
+                    }
+
+                    if daemon.session_id.is_some() {
+                        // Go for next call
+                        Ok(FetchAction::Http)
+                    } else {
+                        Err(FetcherError::Other(
+                            "Can't retrieve `x-transmission-session-id` from remote".to_string(),
+                        ))
+                    }
+                }
+                code => Err(FetcherError::Other(format!(
+                    "Receive error from HTTP remote for login: {code}"
+                ))),
+            }
+        } else {
+            match response.status() {
+                StatusCode::OK => {
+                    let server = response
+                        .headers()
+                        .get(http::header::SERVER)
+                        .and_then(|s| s.to_str().ok().map(|h| h.to_string()));
+                    let body = response
+                        .collect()
+                        .await
+                        .map_err(|e| FetcherError::Hyper(e, server.unwrap_or_default()))?
+                        .aggregate();
+
+                    // Torrents are collected while the response is streamed, to avoid building the whole torrent list
+                    let daemon = self.daemon();
+                    let mut torrents = HashMap::new();
+                    let api_resp = transmission::api::Response::from_reader_visit(
+                        body.reader(),
+                        &mut |torrent: TorrentsArguments| {
+                            if let Some(TorrentId::Id(id)) = &torrent.id {
+                                torrents.insert(id.clone(), torrent);
+                            }
+                        },
+                    )
+                    .map_err(|e| FetcherError::Io(e.into()))?;
+                    daemon.request_id += 1;
+
+                    if let Some(error) = &api_resp.error
+                        && !matches!(daemon.state, TorrentFetchState::TorrentMut(_))
+                    {
+                        return Err(FetcherError::Other(format!(
+                            "Receive error from Transmission: {error}, for state: {:?}",
+                            daemon.state
+                        )));
+                    }
+
+                    // The kept table is only replaced or merged once the response is parsed and successful, to not lose it on error
+                    match daemon.state {
+                        TorrentFetchState::TorrentGet => {
+                            daemon.torrents.clear();
+                            daemon.last_full_sync = Some(Utc::now());
+                        }
+                        TorrentFetchState::TorrentGetRecentlyActive => {
+                            let mut table = std::mem::take(&mut daemon.torrents);
+                            table.extend(torrents);
+                            torrents = table;
+                        }
+                        _ => {}
+                    }
+
+                    match &mut daemon.state {
+                        TorrentFetchState::SessionGet => {
+                            let rpc_dialect = RpcDialect::from_rpc_version(
+                                api_resp.arguments.rpc_version.unwrap_or_default(),
+                            );
+                            info!(
+                                instance = daemon.name,
+                                "Transmission {} with RPC version {:?}, use the {rpc_dialect} dialect",
+                                api_resp.arguments.version.unwrap_or_default(),
+                                api_resp.arguments.rpc_version
+                            );
+                            daemon.rpc_dialect = Some(rpc_dialect);
+                            daemon.state = TorrentFetchState::SessionStats;
+                            Ok(FetchAction::Http)
+                        }
+                        TorrentFetchState::SessionStats => {
+                            if let Some(session_stats) = api_resp.arguments.cumulative_stats {
+                                let name = daemon.name.clone();
+                                self.torrent_stats.send_modify(|torrent_stats| {
+                                    torrent_stats.insert(name, session_stats);
+                                });
+                            }
+
+                            let daemon = self.daemon();
+                            daemon.state = if daemon.need_full_sync() {
+                                TorrentFetchState::TorrentGet
+                            } else {
+                                TorrentFetchState::TorrentGetRecentlyActive
+                            };
+                            Ok(FetchAction::Http)
+                        }
+                        TorrentFetchState::TorrentGet
+                        | TorrentFetchState::TorrentGetRecentlyActive => {
+                            if let Some(removed) = api_resp.arguments.removed {
+                                for id in removed {
+                                    torrents.remove(&id);
+                                }
+                            }
+
+                            let (torrent_mut_list, torrent_files_list) =
+                                self.process_torrents(&mut torrents).await;
+
+                            // Keep the torrent table only for incremental polling
+                            let daemon = self.daemon();
+                            if daemon.incremental_resync().is_some() {
+                                daemon.torrents = torrents;
+                            }
+
+                            if !torrent_files_list.is_empty() {
+                                daemon.state = TorrentFetchState::TorrentFiles(
+                                    torrent_files_list,
+                                    torrent_mut_list,
+                                );
+                                Ok(FetchAction::Http)
+                            } else {
+                                Ok(self.start_mutations(torrent_mut_list))
+                            }
+                        }
+                        TorrentFetchState::TorrentFiles(_, torrent_mut_list) => {
+                            let mut torrent_mut_list = std::mem::take(torrent_mut_list);
+                            torrent_mut_list.extend(
+                                daemon.process_torrent_files(torrents.into_values().collect()),
+                            );
+                            Ok(self.start_mutations(torrent_mut_list))
+                        }
+                        TorrentFetchState::TorrentMut(torrent_list) => {
+                            if !torrent_list.is_empty() {
+                                let method = torrent_list.remove(0);
+                                // Only file rules mutations target torrents by hash
+                                if let transmission::api::Method::TorrentSet(params) = &method
+                                    && api_resp.is_success()
+                                {
+                                    for id in params.ids.iter().flatten() {
+                                        if let TorrentId::Id(Id::Hash(hash)) = id {
+                                            daemon.file_rules_applied.insert(hash.clone());
+                                        }
+                                    }
+                                }
+                                if let Some(history) = &mut daemon.history
+                                    && api_resp.is_success()
+                                {
+                                    history.record_mutation(&method);
+                                    if torrent_list.is_empty()
+                                        && let Err(e) = history.save().await
+                                    {
+                                        warn!(
+                                            instance = daemon.name,
+                                            "Can't write the history: {e}"
+                                        );
+                                    }
+                                }
+
+                                if let transmission::api::Method::TorrentAdd(_) = method {
+                                    if !daemon.watch_folders.process_response(&api_resp).await {
+                                        daemon.feeds.process_response(&api_resp).await;
+                                    }
+                                } else {
+                                    info!(
+                                        instance = daemon.name,
+                                        "Method `{method:?}`, return with {}", api_resp.result
+                                    );
+                                }
+                            }
+
+                            if torrent_list.is_empty() {
+                                Ok(self.next_daemon())
+                            } else {
+                                Ok(FetchAction::Http)
+                            }
+                        }
+                        TorrentFetchState::End => Ok(FetchAction::None),
+                    }
+                }
+                StatusCode::CONFLICT => {
+                    // Try to get the new session ID, and negotiate again the dialect in case Transmission was upgraded
+                    let daemon = self.daemon();
+                    daemon.rpc_dialect = daemon.settings.as_ref().and_then(|s| s.rpc_dialect);
+                    daemon.session_id = if let Some(session_id) =
+                        response.headers().get("x-transmission-session-id")
+                    {
+                        session_id.to_str().map(|s| s.to_string()).ok()
+                    } else {
+                        None
+                    };
+
+                    if let Some(body) = response
+                        .into_body()
+                        .collect()
+                        .await
+                        .ok()
+                        .and_then(|b| String::from_utf8(b.to_bytes().to_vec()).ok())
+                    {
+                        let daemon = self.daemon();
+                        warn!(
+                            instance = daemon.name,
+                            "Transmission session ID[{:?}] expired: {body}", daemon.session_id
+                        );
+                    } else {
+                        let daemon = self.daemon();
+                        warn!(
+                            instance = daemon.name,
+                            "Transmission session ID[{:?}] expired", daemon.session_id
+                        );
+                    }
+
+                    // Continue the flow
+                    Ok(FetchAction::Http)
+                }
+                code => Err(FetcherError::Other(format!(
+                    "Receive error from HTTP remote: {code}, for state: {:?}",
+                    self.daemon().state
+                ))),
+            }
+        }
+    }
+
+    /// Getter of the next event to send to the event service, if any
+    fn next_event<M>(&mut self) -> Option<FetchAction<M>>
+    where
+        M: std::marker::Send + prosa::core::msg::Tvf + std::default::Default,
+    {
+        let (event_service, event) = self.events.pop_front()?;
+        debug!("Send torrent event {event:?} to {event_service}");
+        Some(FetchAction::Srv(event_service, event.to_tvf()))
+    }
+
+    /// Go to the next daemon once the current one is done, or send pending events at the end of the fetch
+    fn next_daemon<M>(&mut self) -> FetchAction<M>
+    where
+        M: std::marker::Send + prosa::core::msg::Tvf + std::default::Default,
+    {
+        self.daemon().state = TorrentFetchState::End;
+        if self.current + 1 < self.daemons.len() {
+            self.current += 1;
+            let daemon = self.daemon();
+            daemon.state = daemon.first_state();
+            FetchAction::Http
+        } else {
+            self.next_event().unwrap_or(FetchAction::None)
+        }
+    }
+
+    /// Start to apply mutations if there are some
+    fn start_mutations<M>(
+        &mut self,
+        torrent_mut_list: Vec<transmission::api::Method>,
+    ) -> FetchAction<M>
+    where
+        M: std::marker::Send + prosa::core::msg::Tvf + std::default::Default,
+    {
+        if torrent_mut_list.is_empty() {
+            self.next_daemon()
+        } else {
+            self.daemon().state = TorrentFetchState::TorrentMut(torrent_mut_list);
+            FetchAction::Http
+        }
+    }
 }
 
 impl<M> FetcherAdaptor<M> for TorrentAdaptor
@@ -126,62 +1084,162 @@ where
     where
         Self: std::marker::Sized,
     {
-        let (meter_status, watch_status) = watch::channel(Vec::new());
-        let _observable_torrent_count =
-            proc.get_proc_param()
-                .meter("transmission")
-                .u64_observable_gauge("prosa_transmission_torrent_count")
-                .with_description("Count of all torrents")
-                .with_callback(move |observer| {
-                    let frequencies = watch_status.borrow().iter().copied().fold(
-                        HashMap::new(),
-                        |mut map, val| {
-                            map.entry(val).and_modify(|frq| *frq += 1).or_insert(1u64);
-                            map
-                        },
-                    );
+        let (meter_status, watch_status) = watch::channel(HashMap::<String, Vec<Status>>::new());
+        let _observable_torrent_count = proc
+            .get_proc_param()
+            .meter("transmission")
+            .u64_observable_gauge("prosa_transmission_torrent_count")
+            .with_description("Count of all torrents")
+            .with_callback(move |observer| {
+                for (instance, torrents_status) in watch_status.borrow().iter() {
+                    let frequencies =
+                        torrents_status
+                            .iter()
+                            .copied()
+                            .fold(HashMap::new(), |mut map, val| {
+                                map.entry(val).and_modify(|frq| *frq += 1).or_insert(1u64);
+                                map
+                            });
 
                     for status in Status::iterator() {
-                        if let Some(torrent_count) = frequencies.get(&status) {
-                            observer.observe(
-                                *torrent_count,
-                                &[KeyValue::new("status", status.to_string())],
-                            );
-                        } else {
-                            observer.observe(0, &[KeyValue::new("status", status.to_string())]);
-                        }
+                        observer.observe(
+                            frequencies.get(&status).copied().unwrap_or_default(),
+                            &[
+                                KeyValue::new("status", status.to_string()),
+                                KeyValue::new("instance", instance.clone()),
+                            ],
+                        );
                     }
-                })
-                .build();
+                }
+            })
+            .build();
 
-        let (meter_stats, watch_stats) = watch::channel(SessionStats::default());
+        let (meter_stats, watch_stats) = watch::channel(HashMap::<String, SessionStats>::new());
         let _observable_session_stats = proc
             .get_proc_param()
             .meter("transmission")
             .u64_observable_counter("prosa_transmission_session_stats")
             .with_description("Stats of the session")
             .with_callback(move |observer| {
-                let stats = watch_stats.borrow();
-                observer.observe(stats.uploaded_bytes, &[KeyValue::new("flow", "send")]);
-                observer.observe(stats.downloaded_bytes, &[KeyValue::new("flow", "recv")]);
+                for (instance, stats) in watch_stats.borrow().iter() {
+                    observer.observe(
+                        stats.uploaded_bytes,
+                        &[
+                            KeyValue::new("flow", "send"),
+                            KeyValue::new("instance", instance.clone()),
+                        ],
+                    );
+                    observer.observe(
+                        stats.downloaded_bytes,
+                        &[
+                            KeyValue::new("flow", "recv"),
+                            KeyValue::new("instance", instance.clone()),
+                        ],
+                    );
+                }
             })
             .build();
 
+        let (meter_pinned, watch_pinned) = watch::channel(HashMap::<String, u64>::new());
+        let _observable_pinned_count = proc
+            .get_proc_param()
+            .meter("transmission")
+            .u64_observable_gauge("prosa_transmission_pinned_count")
+            .with_description("Count of pinned torrents, excluded from automated mutations")
+            .with_callback(move |observer| {
+                for (instance, pinned_count) in watch_pinned.borrow().iter() {
+                    observer.observe(
+                        *pinned_count,
+                        &[KeyValue::new("instance", instance.clone())],
+                    );
+                }
+            })
+            .build();
+
+        let (meter_obligations, watch_obligations) = watch::channel(HashMap::<String, u64>::new());
+        let _observable_obligations_at_risk = proc
+            .get_proc_param()
+            .meter("transmission")
+            .u64_observable_gauge("prosa_transmission_obligations_at_risk")
+            .with_description(
+                "Count of stopped or stalled torrents that still owe seed time or ratio",
+            )
+            .with_callback(move |observer| {
+                for (instance, at_risk) in watch_obligations.borrow().iter() {
+                    observer.observe(*at_risk, &[KeyValue::new("instance", instance.clone())]);
+                }
+            })
+            .build();
+
+        let (meter_tracker_transfer, watch_tracker_transfer) =
+            watch::channel(HashMap::<String, BTreeMap<String, Transfer>>::new());
+        let _observable_tracker_transfer = proc
+            .get_proc_param()
+            .meter("transmission")
+            .u64_observable_counter("prosa_transmission_tracker_transfer")
+            .with_description("Lifetime transfer by tracker, including removed torrents")
+            .with_callback(move |observer| {
+                for (instance, trackers) in watch_tracker_transfer.borrow().iter() {
+                    for (tracker, transfer) in trackers {
+                        observer.observe(
+                            transfer.uploaded,
+                            &[
+                                KeyValue::new("flow", "send"),
+                                KeyValue::new("tracker", tracker.clone()),
+                                KeyValue::new("instance", instance.clone()),
+                            ],
+                        );
+                        observer.observe(
+                            transfer.downloaded,
+                            &[
+                                KeyValue::new("flow", "recv"),
+                                KeyValue::new("tracker", tracker.clone()),
+                                KeyValue::new("instance", instance.clone()),
+                            ],
+                        );
+                    }
+                }
+            })
+            .build();
+
+        let daemons = match proc
+            .settings
+            .get_adaptor_config::<TorrentAdaptorSettings>()
+            .ok()
+        {
+            Some(adaptor_settings) if !adaptor_settings.daemons.is_empty() => adaptor_settings
+                .daemons
+                .into_iter()
+                .map(|d| TransmissionDaemon::new(d.name, &d.rpc_path, Some(d.settings)))
+                .collect::<Result<Vec<_>, _>>(),
+            adaptor_settings => TransmissionDaemon::new(
+                DEFAULT_INSTANCE.to_string(),
+                DEFAULT_RPC_PATH,
+                adaptor_settings.map(|s| s.settings),
+            )
+            .map(|daemon| vec![daemon]),
+        }
+        .map_err(FetcherError::Other)?;
+
         Ok(Self {
-            transmission_uri: "/transmission/rpc".parse::<hyper::Uri>().map_err(|e| {
-                FetcherError::Other(format!("can't parse hyper::uri `/transmission/rpc` : {e}"))
-            })?,
-            settings: proc.settings.get_adaptor_config::<TorrentSettings>().ok(),
-            state: TorrentFetchState::default(),
-            session_id: None,
+            daemons,
+            current: 0,
+            // Transmission dates are in seconds
+            started: Utc::now().trunc_subsecs(0),
+            events: VecDeque::new(),
             torrent_count: meter_status,
             torrent_stats: meter_stats,
+            pinned_count: meter_pinned,
+            obligations_at_risk: meter_obligations,
+            tracker_transfer: meter_tracker_transfer,
         })
     }
 
     fn fetch(&mut self) -> Result<FetchAction<M>, FetcherError<M>> {
-        // Call HTTP to retrieve torrents with first state
-        self.state = TorrentFetchState::default();
+        // Call HTTP to retrieve torrents of the first daemon with its first state
+        self.current = 0;
+        let daemon = self.daemon();
+        daemon.state = daemon.first_state();
         Ok(FetchAction::Http)
     }
 
@@ -189,18 +1247,30 @@ where
         &self,
         mut request_builder: http::request::Builder,
     ) -> Result<Request<BoxBody<hyper::body::Bytes, Infallible>>, FetcherError<M>> {
-        if let Some(session_id) = &self.session_id {
-            if let Some(method) = self.state.get_method() {
+        let daemon = &self.daemons[self.current];
+        if let Some(session_id) = &daemon.session_id {
+            if let Some(mut method) = daemon.state.get_method(&daemon.torrent_fields) {
+                if let transmission::api::Method::TorrentGet(_, _, format) = &mut method
+                    && daemon.settings.as_ref().is_some_and(|s| s.table_format)
+                {
+                    *format = Some(TorrentGetFormat::Table);
+                }
+
                 request_builder = request_builder
                     .method(Method::POST)
-                    .uri(self.transmission_uri.clone())
+                    .uri(daemon.transmission_uri.clone())
                     .header(hyper::header::CONNECTION, "keep-alive")
                     .header(hyper::header::CONTENT_TYPE, "application/json")
                     .header(hyper::header::ACCEPT, "application/json")
                     .header("X-Transmission-Session-Id", session_id);
 
                 let request = request_builder.body(BoxBody::new(Full::new(Bytes::from(
-                    serde_json::to_vec(&method).map_err(|e| {
+                    serde_json::to_vec(&transmission::api::Request::new(
+                        &method,
+                        daemon.rpc_dialect.unwrap_or_default(),
+                        Some(daemon.request_id),
+                    ))
+                    .map_err(|e| {
                         FetcherError::Other(format!("can't serialize transmission method: {e}"))
                     })?,
                 ))))?;
@@ -214,7 +1284,7 @@ where
         } else {
             request_builder = request_builder
                 .method(Method::GET)
-                .uri(self.transmission_uri.clone())
+                .uri(daemon.transmission_uri.clone())
                 .header(hyper::header::CONNECTION, "keep-alive")
                 .header(hyper::header::ACCEPT, "application/json");
             let request = request_builder.body(BoxBody::default())?;
@@ -227,173 +1297,14 @@ where
         response: Result<Response<Incoming>, FetcherError<M>>,
     ) -> Result<FetchAction<M>, FetcherError<M>> {
         match response {
-            Ok(response) => {
-                if self.session_id.is_none() {
-                    match response.status() {
-                        StatusCode::OK | StatusCode::CONFLICT => {
-                            if let Some(session_id) =
-                                response.headers().get("x-transmission-session-id")
-                            {
-                                self.session_id = session_id.to_str().map(|s| s.to_string()).ok();
-                            }
-
-                            if self.session_id.is_some() {
-                                // Go for next call
-                                Ok(FetchAction::Http)
-                            } else {
-                                Err(FetcherError::Other(
-                                    "Can't retrieve `x-transmission-session-id` from remote"
-                                        .to_string(),
-                                ))
-                            }
-                        }
-                        code => Err(FetcherError::Other(format!(
-                            "Receive error from HTTP remote for login: {code}"
-                        ))),
-                    }
-                } else {
-                    match response.status() {
-                        StatusCode::OK => {
-                            let server = response
-                                .headers()
-                                .get(http::header::SERVER)
-                                .and_then(|s| s.to_str().ok().map(|h| h.to_string()));
-                            let body = response
-                                .collect()
-                                .await
-                                .map_err(|e| FetcherError::Hyper(e, server.unwrap_or_default()))?
-                                .aggregate();
-                            let api_resp: transmission::api::Response =
-                                serde_json::from_reader(body.reader())
-                                    .map_err(|e| FetcherError::Io(e.into()))?;
-
-                            match &mut self.state {
-                                TorrentFetchState::SessionStats => {
-                                    if let Some(session_stats) = api_resp.arguments.cumulative_stats
-                                    {
-                                        let _ = self.torrent_stats.send(session_stats);
-                                    }
-
-                                    self.state = TorrentFetchState::TorrentGet;
-                                    Ok(FetchAction::Http)
-                                }
-                                TorrentFetchState::TorrentGet => {
-                                    let mut torrent_no_peer_list = Vec::new();
-                                    let mut torrent_rm_list = Vec::new();
-                                    let mut torrents_status =
-                                        Vec::with_capacity(api_resp.arguments.torrents.len());
-                                    for torrent in api_resp.arguments.torrents {
-                                        if let Some(status) = torrent.status {
-                                            torrents_status.push(status);
-                                        }
-
-                                        if let Some(torrent_settings) = &self.settings
-                                            && let Some(torrent_id) = torrent.id
-                                        {
-                                            if !torrent.upload_limited
-                                                && torrent.tracker_list.is_some_and(|t| {
-                                                    !torrent_settings.tracker_allowed(t)
-                                                })
-                                            {
-                                                torrent_no_peer_list.push(torrent_id);
-                                            } else if torrent.added_date.is_some_and(|d| {
-                                                torrent_settings.need_removal(d, torrent.is_private)
-                                            }) {
-                                                torrent_rm_list.push(torrent_id);
-                                            }
-                                        }
-                                    }
-
-                                    let _ = self.torrent_count.send(torrents_status);
-
-                                    if !torrent_no_peer_list.is_empty()
-                                        || !torrent_rm_list.is_empty()
-                                    {
-                                        let mut torrent_mut_list = Vec::new();
-
-                                        if !torrent_no_peer_list.is_empty() {
-                                            torrent_mut_list.push(
-                                                transmission::api::Method::TorrentSet(Box::new(
-                                                    TorrentSetParams {
-                                                        ids: Some(torrent_no_peer_list),
-                                                        peer_limit: Some(0),
-                                                        upload_limit: Some(0),
-                                                        upload_limited: Some(true),
-                                                        ..Default::default()
-                                                    },
-                                                )),
-                                            );
-                                        }
-
-                                        if !torrent_rm_list.is_empty() {
-                                            torrent_mut_list.push(
-                                                transmission::api::Method::TorrentRemove(
-                                                    torrent_rm_list,
-                                                    false,
-                                                ),
-                                            );
-                                        }
-
-                                        self.state =
-                                            TorrentFetchState::TorrentMut(torrent_mut_list);
-                                        Ok(FetchAction::Http)
-                                    } else {
-                                        self.state = TorrentFetchState::End;
-                                        Ok(FetchAction::None)
-                                    }
-                                }
-                                TorrentFetchState::TorrentMut(torrent_list) => {
-                                    if let Some(method) = torrent_list.pop() {
-                                        info!(
-                                            "Method `{method:?}`, return with {}",
-                                            api_resp.result
-                                        );
-                                    }
-
-                                    if torrent_list.is_empty() {
-                                        Ok(FetchAction::None)
-                                    } else {
-                                        Ok(FetchAction::Http)
-                                    }
-                                }
-                                TorrentFetchState::End => Ok(FetchAction::None),
-                            }
-                        }
-                        StatusCode::CONFLICT => {
-                            // Try to get the new session ID
-                            self.session_id = if let Some(session_id) =
-                                response.headers().get("x-transmission-session-id")
-                            {
-                                session_id.to_str().map(|s| s.to_string()).ok()
-                            } else {
-                                None
-                            };
-
-                            if let Some(body) = response
-                                .into_body()
-                                .collect()
-                                .await
-                                .ok()
-                                .and_then(|b| String::from_utf8(b.to_bytes().to_vec()).ok())
-                            {
-                                warn!(
-                                    "Transmission session ID[{:?}] expired: {body}",
-                                    self.session_id
-                                );
-                            } else {
-                                warn!("Transmission session ID[{:?}] expired", self.session_id);
-                            }
-
-                            // Continue the flow
-                            Ok(FetchAction::Http)
-                        }
-                        code => Err(FetcherError::Other(format!(
-                            "Receive error from HTTP remote: {code}, for state: {:?}",
-                            self.state
-                        ))),
-                    }
+            Ok(response) => match self.process_daemon_response(response).await {
+                // An error on a daemon should not prevent to fetch the others
+                Err(e) if self.current + 1 < self.daemons.len() => {
+                    warn!(instance = self.daemon().name, "{e}");
+                    Ok(self.next_daemon())
                 }
-            }
+                action => action,
+            },
             Err(FetcherError::Hyper(he, addr)) => {
                 if he.is_canceled() {
                     debug!(addr = addr, "HTTP error {:?}", he);
@@ -406,4 +1317,300 @@ where
             Err(e) => Err(e),
         }
     }
+
+    fn process_service_response(
+        &mut self,
+        _response: prosa::core::msg::ResponseMsg<M>,
+    ) -> Result<FetchAction<M>, FetcherError<M>> {
+        // Continue to send pending events
+        Ok(self.next_event().unwrap_or(FetchAction::None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use hyper_util::rt::TokioIo;
+    use prosa::core::{
+        main::{MainProc, MainRunnable as _},
+        proc::ProcConfig,
+        settings::settings,
+    };
+    use prosa_fetcher::proc::FetcherSettings;
+    use prosa_utils::msg::simple_string_tvf::SimpleStringTvf;
+    use serde::Serialize;
+    use serde_json::json;
+
+    use crate::transmission::mock::{MockFailure, MockTransmission};
+
+    use super::*;
+
+    #[settings]
+    #[derive(Default, Debug, Serialize)]
+    struct Settings {}
+
+    /// Create the adaptor with its configuration written in a temporary file
+    fn adaptor(name: &str, config: &str) -> TorrentAdaptor {
+        let config_path =
+            std::env::temp_dir().join(format!("prosa-ovserver-{name}-{}.yml", std::process::id()));
+        std::fs::write(&config_path, config).unwrap();
+        let settings: FetcherSettings =
+            serde_json::from_value(json!({ "adaptor_config_path": config_path })).unwrap();
+        let (bus, _main) = MainProc::<SimpleStringTvf>::create(&Settings::default(), Some(1));
+        let proc = FetcherProc::<SimpleStringTvf>::create(1, name.to_string(), bus, settings);
+        FetcherAdaptor::<SimpleStringTvf>::new(&proc).unwrap()
+    }
+
+    /// Run a whole fetch against the mock, like the fetcher processor does
+    async fn fetch(
+        adaptor: &mut TorrentAdaptor,
+        addr: SocketAddr,
+    ) -> Result<(), FetcherError<SimpleStringTvf>> {
+        let stream = tokio::net::TcpStream::connect(addr).await?;
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| FetcherError::Hyper(e, addr.to_string()))?;
+        tokio::spawn(connection);
+
+        let mut action = FetcherAdaptor::<SimpleStringTvf>::fetch(adaptor)?;
+        while let FetchAction::Http = action {
+            let request = FetcherAdaptor::<SimpleStringTvf>::create_http_request(
+                adaptor,
+                Request::builder(),
+            )?;
+            let response = sender
+                .send_request(request)
+                .await
+                .map_err(|e| FetcherError::Hyper(e, addr.to_string()))?;
+            action = adaptor.process_http_response(Ok(response)).await?;
+        }
+        Ok(())
+    }
+
+    /// Timestamp of a date added some days ago
+    fn added_date(days: i64) -> i64 {
+        (Utc::now() - Duration::days(days)).timestamp()
+    }
+
+    /// Mock with a torrent to remove, a public one to limit, and a recent one to keep
+    fn mock() -> MockTransmission {
+        MockTransmission::new(vec![
+            json!({ "id": 1, "name": "Old", "status": 6, "trackerList": "https://private.example/announce", "addedDate": added_date(60), "uploadLimited": false }),
+            json!({ "id": 2, "name": "Public", "status": 6, "trackerList": "https://public.example/announce", "addedDate": added_date(1), "uploadLimited": false }),
+            json!({ "id": 3, "name": "Recent", "status": 4, "trackerList": "https://private.example/announce", "addedDate": added_date(1), "uploadLimited": false }),
+        ])
+    }
+
+    const CONFIG: &str = "tracker_allowlist: [\"private.example\"]\nremove_after: 30\nincremental_resync: 60\ntable_format: true\n";
+
+    #[tokio::test]
+    async fn adaptor_handshake() {
+        let mock = mock();
+        let addr = mock.serve().await.unwrap();
+        let mut adaptor = adaptor("adaptor_handshake", CONFIG);
+
+        // Handshake and negotiation of the dialect
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(
+            vec!["session-get", "session-stats", "torrent-get"],
+            mock.methods()[..3]
+        );
+        assert_eq!(
+            Some("mock-session-1"),
+            adaptor.daemons[0].session_id.as_deref()
+        );
+        assert_eq!(Some(RpcDialect::Legacy), adaptor.daemons[0].rpc_dialect);
+
+        // A new session id is negotiated transparently
+        mock.rotate_session_id();
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(
+            Some("mock-session-2"),
+            adaptor.daemons[0].session_id.as_deref()
+        );
+    }
+
+    #[tokio::test]
+    async fn adaptor_mutations() {
+        let mock = mock();
+        let addr = mock.serve().await.unwrap();
+        let mut adaptor = adaptor("adaptor_mutations", CONFIG);
+
+        // Public torrents are limited, and old ones are removed
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(
+            vec![
+                "session-get",
+                "session-stats",
+                "torrent-get",
+                "torrent-set",
+                "torrent-remove"
+            ],
+            mock.methods()
+        );
+        let torrents = mock.torrents();
+        assert_eq!(
+            vec![json!(2), json!(3)],
+            torrents.iter().map(|t| t["id"].clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(json!(true), torrents[0]["uploadLimited"]);
+        assert_eq!(2, adaptor.daemons[0].torrents.len());
+    }
+
+    #[tokio::test]
+    async fn adaptor_incremental_polling() {
+        let mock = mock();
+        let addr = mock.serve().await.unwrap();
+        let mut adaptor = adaptor("adaptor_incremental_polling", CONFIG);
+        fetch(&mut adaptor, addr).await.unwrap();
+
+        // Only recently active torrents are polled after the full sync
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(
+            Some(&json!("recently-active")),
+            mock.requests()
+                .last()
+                .and_then(|r| r["arguments"].get("ids"))
+        );
+        assert_eq!(2, adaptor.daemons[0].torrents.len());
+
+        // A malformed response keep the torrent table for the next incremental polling
+        mock.fail_on(
+            "torrent-get",
+            MockFailure::Body(r#"{"arguments":{"torrents":[{"id":2,"#.to_string()),
+        );
+        assert!(fetch(&mut adaptor, addr).await.is_err());
+        assert_eq!(2, adaptor.daemons[0].torrents.len());
+    }
+
+    #[tokio::test]
+    async fn adaptor_errors() {
+        let mock = mock();
+        let addr = mock.serve().await.unwrap();
+        let mut adaptor = adaptor("adaptor_errors", CONFIG);
+
+        // HTTP errors of Transmission stop the fetch
+        mock.fail_next(MockFailure::Http(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(fetch(&mut adaptor, addr).await.is_err());
+
+        // An error result of the legacy dialect on the handshake doesn't stop the fetch
+        mock.fail_next(MockFailure::Result("unrecognized method".to_string()));
+        fetch(&mut adaptor, addr).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn adaptor_daemons() {
+        let mock = mock();
+        let addr = mock.serve().await.unwrap();
+        let mut adaptor = adaptor(
+            "adaptor_daemons",
+            "daemons:\n  - name: first\n  - name: second\n",
+        );
+
+        // A malformed response of a daemon doesn't prevent to fetch the next ones
+        mock.fail_on("torrent-get", MockFailure::Body("{".to_string()));
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(
+            2,
+            mock.methods()
+                .iter()
+                .filter(|m| *m == "torrent-get")
+                .count()
+        );
+        assert_eq!(1, adaptor.current);
+    }
+
+    #[tokio::test]
+    async fn adaptor_json_rpc() {
+        let mock = mock().with_dialect(RpcDialect::JsonRpc);
+        let addr = mock.serve().await.unwrap();
+        let mut adaptor = adaptor("adaptor_json_rpc", CONFIG);
+
+        // The dialect is negotiated with the legacy `session-get`, then every request use JSON-RPC 2.0
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(Some(RpcDialect::JsonRpc), adaptor.daemons[0].rpc_dialect);
+        assert_eq!(
+            vec![
+                "session-get",
+                "session_stats",
+                "torrent_get",
+                "torrent_set",
+                "torrent_remove"
+            ],
+            mock.methods()
+        );
+        assert!(
+            mock.requests()[1..]
+                .iter()
+                .all(|r| r["jsonrpc"] == "2.0" && r.get("arguments").is_none())
+        );
+        let torrents = mock.torrents();
+        assert_eq!(
+            vec![json!(2), json!(3)],
+            torrents.iter().map(|t| t["id"].clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(json!(true), torrents[0]["uploadLimited"]);
+        assert_eq!(2, adaptor.daemons[0].torrents.len());
+
+        // Incremental polling, and errors returned as JSON-RPC error objects
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(
+            Some(&json!("recently_active")),
+            mock.requests().last().and_then(|r| r["params"].get("ids"))
+        );
+        assert_eq!(2, adaptor.daemons[0].torrents.len());
+        mock.fail_on("torrent-get", MockFailure::Result("busy".to_string()));
+        assert!(fetch(&mut adaptor, addr).await.is_err());
+        assert_eq!(2, adaptor.daemons[0].torrents.len());
+    }
+
+    #[tokio::test]
+    async fn adaptor_file_rules() {
+        let mut adaptor = adaptor(
+            "adaptor_file_rules",
+            "file_rules:\n  unwanted: [\"*.nfo\"]\n",
+        );
+        let mock = MockTransmission::new(vec![
+            json!({ "id": 4, "hashString": "d", "name": "Show", "status": 4, "addedDate": added_date(0), "metadataPercentComplete": 1.0, "uploadLimited": false,
+                "files": [
+                    { "bytesCompleted": 0, "length": 40000, "name": "Show/Show.S01E01.mkv" },
+                    { "bytesCompleted": 0, "length": 1000, "name": "Show/Show.nfo" }
+                ],
+                "fileStats": [
+                    { "bytesCompleted": 0, "wanted": true, "priority": 0 },
+                    { "bytesCompleted": 0, "wanted": true, "priority": 0 }
+                ] }),
+            json!({ "id": 5, "hashString": "e", "name": "Manual", "status": 4, "addedDate": added_date(2), "metadataPercentComplete": 1.0, "uploadLimited": false,
+                "files": [
+                    { "bytesCompleted": 0, "length": 40000, "name": "Show/Show.S01E01.mkv" },
+                    { "bytesCompleted": 0, "length": 1000, "name": "Show/Show.nfo" }
+                ],
+                "fileStats": [
+                    { "bytesCompleted": 0, "wanted": true, "priority": 0 },
+                    { "bytesCompleted": 0, "wanted": true, "priority": 0 }
+                ] }),
+        ]);
+        let addr = mock.serve().await.unwrap();
+        mock.fail_on("torrent-set", MockFailure::Result("no space".to_string()));
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(None, mock.torrents()[0].get("files-unwanted"));
+        assert!(adaptor.daemons[0].file_rules_applied.is_empty());
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(json!([1]), mock.torrents()[0]["files-unwanted"]);
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert_eq!(
+            2,
+            mock.methods()
+                .iter()
+                .filter(|m| *m == "torrent-set")
+                .count()
+        );
+        assert_eq!(None, mock.torrents()[1].get("files-unwanted"));
+
+        // Torrents removed from Transmission are forgotten
+        let addr = MockTransmission::new(Vec::new()).serve().await.unwrap();
+        fetch(&mut adaptor, addr).await.unwrap();
+        assert!(adaptor.daemons[0].file_rules_applied.is_empty());
+    }
 }
@@ -1,8 +1,11 @@
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
-use base64::{Engine as _, engine::general_purpose::STANDARD as base64};
 use chrono::{DateTime, TimeDelta, Utc};
-use serde::{Deserialize, Deserializer, Serialize, Serializer, ser::SerializeMap as _};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+    ser::{self, SerializeMap as _},
+};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use serde_with::skip_serializing_none;
 
@@ -22,33 +25,466 @@ where
     map.end()
 }
 
-fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+/// Timestamp in seconds, where 0 (`UNIX_EPOCH`) is no date. It's kept to serialize it back as it was
+mod timestamp {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize as _, Deserializer, Serialize as _, Serializer};
+
+    pub fn serialize<S>(date: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        date.map(|d| d.timestamp())
+            .unwrap_or_default()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<i64>::deserialize(deserializer)?.and_then(DateTime::from_timestamp_secs))
+    }
+}
+
+/// Duration in seconds, where a negative value is unknown (`-1` not available, `-2` unknown). It's kept to serialize it back as it was
+mod duration {
+    use chrono::TimeDelta;
+    use serde::{Deserialize as _, Deserializer, Serialize as _, Serializer};
+
+    pub fn serialize<S>(time: &Option<TimeDelta>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        time.map(|t| t.num_seconds())
+            .unwrap_or(-1)
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<TimeDelta>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<i64>::deserialize(deserializer)?.and_then(TimeDelta::try_seconds))
+    }
+}
+
+/// Torrent entry of a `torrent-get` response, either an object or a table row
+enum TorrentRow {
+    Torrent(Box<TorrentsArguments>),
+    Row(Vec<serde_json::Value>),
+}
+
+impl<'de> Deserialize<'de> for TorrentRow {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TorrentRowVisitor;
+
+        impl<'de> Visitor<'de> for TorrentRowVisitor {
+            type Value = TorrentRow;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a torrent object or a table row")
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                Ok(TorrentRow::Torrent(Box::new(
+                    TorrentsArguments::deserialize(de::value::MapAccessDeserializer::new(map))?,
+                )))
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(TorrentRow::Row(Vec::deserialize(
+                    de::value::SeqAccessDeserializer::new(seq),
+                )?))
+            }
+        }
+
+        deserializer.deserialize_any(TorrentRowVisitor)
+    }
+}
+
+/// Decoder of torrent entries, in object or table format
+#[derive(Default)]
+struct TorrentTable {
+    /// Dialect of the table keys
+    dialect: RpcDialect,
+    /// Keys of the table, from its first row
+    keys: Option<Vec<String>>,
+}
+
+impl TorrentTable {
+    /// Decode a torrent entry. Return `None` for the table header row
+    fn decode(&mut self, row: TorrentRow) -> Result<Option<TorrentsArguments>, serde_json::Error> {
+        match row {
+            TorrentRow::Torrent(torrent) => Ok(Some(*torrent)),
+            TorrentRow::Row(row) => {
+                if let Some(keys) = &self.keys {
+                    TorrentsArguments::from_table_row(keys, row).map(Some)
+                } else {
+                    // The first row of the table contain the keys
+                    self.keys = Some(
+                        row.into_iter()
+                            .map(|k| match k {
+                                serde_json::Value::String(k)
+                                    if self.dialect == RpcDialect::JsonRpc =>
+                                {
+                                    Ok(legacy_key(&k).into_owned())
+                                }
+                                serde_json::Value::String(k) => Ok(k),
+                                k => Err(de::Error::custom(format!("invalid table key `{k}`"))),
+                            })
+                            .collect::<Result<_, _>>()?,
+                    );
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+
+/// Deserialize torrents in object or table format
+fn deserialize_torrents<'de, D>(deserializer: D) -> Result<Vec<TorrentsArguments>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    Ok(Option::<i64>::deserialize(deserializer)?.and_then(|ts| {
-        if ts > 0 {
-            DateTime::from_timestamp_secs(ts)
-        } else {
-            None
+    let rows = Vec::<TorrentRow>::deserialize(deserializer)?;
+    let mut torrents = Vec::with_capacity(rows.len());
+    let mut table = TorrentTable::default();
+    for row in rows {
+        if let Some(torrent) = table.decode(row).map_err(de::Error::custom)? {
+            torrents.push(torrent);
         }
-    }))
+    }
+
+    Ok(torrents)
+}
+
+/// Visitor of torrents, to process a `torrent-get` response without building the whole torrent list
+pub trait TorrentVisitor {
+    /// Method called for every torrent of the response
+    fn visit_torrent(&mut self, torrent: TorrentsArguments);
 }
 
-fn deserialize_time<'de, D>(deserializer: D) -> Result<Option<TimeDelta>, D::Error>
+impl<F> TorrentVisitor for F
 where
-    D: Deserializer<'de>,
+    F: FnMut(TorrentsArguments),
 {
-    Ok(Option::<i64>::deserialize(deserializer)?
-        .and_then(|ts| if ts > 0 { TimeDelta::new(ts, 0) } else { None }))
+    fn visit_torrent(&mut self, torrent: TorrentsArguments) {
+        self(torrent)
+    }
 }
 
-fn deserialize_bitfield<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+/// Seed to deserialize a response while visiting its torrents
+struct ResponseSeed<'v, V>(&'v mut V);
+
+impl<'de, V> de::DeserializeSeed<'de> for ResponseSeed<'_, V>
 where
-    D: Deserializer<'de>,
+    V: TorrentVisitor,
+{
+    type Value = Response;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, V> Visitor<'de> for ResponseSeed<'_, V>
+where
+    V: TorrentVisitor,
+{
+    type Value = Response;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a transmission response")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut arguments = None;
+        let mut result = None;
+        let mut tag = None;
+        let mut error: Option<RpcError> = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "arguments" => {
+                    arguments =
+                        Some(map.next_value_seed(ArgumentsSeed(&mut *self.0, RpcDialect::Legacy))?)
+                }
+                // The result is a status string for the legacy dialect, and the arguments for JSON-RPC
+                "result" => match map.next_value_seed(ResultSeed(&mut *self.0))? {
+                    RpcResult::Status(status) => result = Some(status),
+                    RpcResult::Arguments(args) => {
+                        arguments = Some(*args);
+                        result = Some("success".to_string());
+                    }
+                },
+                "tag" | "id" => tag = map.next_value()?,
+                "error" => error = map.next_value()?,
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        if let Some(error) = &error {
+            result = Some(error.message.clone());
+            arguments.get_or_insert_default();
+        }
+
+        Ok(Response {
+            arguments: arguments.ok_or_else(|| de::Error::missing_field("arguments"))?,
+            result: result.ok_or_else(|| de::Error::missing_field("result"))?,
+            tag,
+            error,
+        })
+    }
+}
+
+/// Result of a response, depending of the dialect
+enum RpcResult {
+    /// Status string of the legacy dialect
+    Status(String),
+    /// Arguments of a JSON-RPC response
+    Arguments(Box<ResponseArguments>),
+}
+
+/// Seed to deserialize the result of a response in both dialects
+struct ResultSeed<'v, V>(&'v mut V);
+
+impl<'de, V> de::DeserializeSeed<'de> for ResultSeed<'_, V>
+where
+    V: TorrentVisitor,
+{
+    type Value = RpcResult;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, V> Visitor<'de> for ResultSeed<'_, V>
+where
+    V: TorrentVisitor,
+{
+    type Value = RpcResult;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a result string or JSON-RPC result arguments")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RpcResult::Status(v.to_string()))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RpcResult::Arguments(Box::default()))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        ArgumentsSeed(self.0, RpcDialect::JsonRpc)
+            .visit_map(map)
+            .map(|arguments| RpcResult::Arguments(Box::new(arguments)))
+    }
+}
+
+/// Seed to deserialize response arguments while visiting its torrents
+struct ArgumentsSeed<'v, V>(&'v mut V, RpcDialect);
+
+impl<'de, V> de::DeserializeSeed<'de> for ArgumentsSeed<'_, V>
+where
+    V: TorrentVisitor,
+{
+    type Value = ResponseArguments;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, V> Visitor<'de> for ArgumentsSeed<'_, V>
+where
+    V: TorrentVisitor,
 {
-    let encoded: &str = Deserialize::deserialize(deserializer)?;
-    Ok(base64.decode(encoded).ok())
+    type Value = ResponseArguments;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("transmission response arguments")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        // Other arguments are small, so they are kept to be deserialized at the end
+        let mut arguments = serde_json::Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "torrents" {
+                map.next_value_seed(TorrentsSeed(&mut *self.0, self.1))?;
+            } else if self.1 == RpcDialect::JsonRpc {
+                let mut value = map.next_value()?;
+                map_keys(&mut value, &legacy_key);
+                arguments.insert(legacy_key(&key).into_owned(), value);
+            } else {
+                arguments.insert(key, map.next_value()?);
+            }
+        }
+
+        ResponseArguments::deserialize(serde_json::Value::Object(arguments))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Seed to visit torrents one by one
+struct TorrentsSeed<'v, V>(&'v mut V, RpcDialect);
+
+impl<'de, V> de::DeserializeSeed<'de> for TorrentsSeed<'_, V>
+where
+    V: TorrentVisitor,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, V> Visitor<'de> for TorrentsSeed<'_, V>
+where
+    V: TorrentVisitor,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of torrents")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut table = TorrentTable {
+            dialect: self.1,
+            ..Default::default()
+        };
+        if self.1 == RpcDialect::JsonRpc {
+            // Keys are renamed in their legacy name for every torrent
+            while let Some(row) = seq.next_element::<serde_json::Value>()? {
+                let row = match row {
+                    serde_json::Value::Array(row) => TorrentRow::Row(row),
+                    mut torrent => {
+                        map_keys(&mut torrent, &legacy_key);
+                        TorrentRow::Torrent(Box::new(
+                            TorrentsArguments::deserialize(torrent).map_err(de::Error::custom)?,
+                        ))
+                    }
+                };
+                if let Some(torrent) = table.decode(row).map_err(de::Error::custom)? {
+                    self.0.visit_torrent(torrent);
+                }
+            }
+        } else {
+            while let Some(row) = seq.next_element::<TorrentRow>()? {
+                if let Some(torrent) = table.decode(row).map_err(de::Error::custom)? {
+                    self.0.visit_torrent(torrent);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Wanted flags are booleans since Transmission 4, and 0/1 integers before
+mod wanted {
+    use serde::{Deserialize, Deserializer, Serialize as _, Serializer};
+
+    pub fn serialize<S>(wanted: &Option<Vec<bool>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        wanted.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<bool>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Wanted {
+            Bool(bool),
+            Int(u8),
+        }
+
+        Ok(
+            Option::<Vec<Wanted>>::deserialize(deserializer)?.map(|wanted| {
+                wanted
+                    .into_iter()
+                    .map(|w| match w {
+                        Wanted::Bool(wanted) => wanted,
+                        Wanted::Int(wanted) => wanted != 0,
+                    })
+                    .collect()
+            }),
+        )
+    }
+}
+
+/// Bitfield encoded in base64
+mod bitfield {
+    use base64::{Engine as _, engine::general_purpose::STANDARD as base64};
+    use serde::{Deserialize as _, Deserializer, Serialize as _, Serializer};
+
+    pub fn serialize<S>(bitfield: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        bitfield
+            .as_ref()
+            .map(|b| base64.encode(b))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Owned, because torrents of JSON-RPC and table responses are decoded from JSON values
+        let encoded = String::deserialize(deserializer)?;
+        Ok(base64.decode(encoded).ok())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -65,12 +501,20 @@ pub enum Method {
     TorrentReannounce(Option<TorrentId>),
     /// torrent mutator
     TorrentSet(Box<TorrentSetParams>),
-    /// torrent getter
-    TorrentGet(Vec<TorrentField>, Option<Vec<TorrentId>>),
+    /// torrent getter, optionally with the table response format to lower the payload size
+    TorrentGet(
+        Vec<TorrentField>,
+        Option<Vec<TorrentId>>,
+        Option<TorrentGetFormat>,
+    ),
     /// remove torrent, delete local data if bool is true
     TorrentRemove(Vec<TorrentId>, bool),
+    /// add a torrent from a file, an URL, a magnet link or a metainfo
+    TorrentAdd(Box<TorrentAddParams>),
     /// Session statistics for all torrents
     SessionStats,
+    /// Session getter, only for the RPC version to negotiate the dialect
+    SessionGet,
 }
 
 impl Serialize for Method {
@@ -94,11 +538,17 @@ impl Serialize for Method {
                 map.serialize_entry("arguments", &params)?;
                 map.end()
             }
-            Method::TorrentGet(fields, ids) => {
-                let mut map =
-                    serializer.serialize_map(if ids.is_some() { Some(2) } else { Some(1) })?;
+            Method::TorrentGet(fields, ids, format) => {
+                let mut map = serializer.serialize_map(Some(2))?;
                 map.serialize_entry("method", "torrent-get")?;
-                map.serialize_entry("arguments", &ArgumentsTorrentGet { fields, ids })?;
+                map.serialize_entry(
+                    "arguments",
+                    &ArgumentsTorrentGet {
+                        fields,
+                        ids,
+                        format: *format,
+                    },
+                )?;
                 map.end()
             }
             Method::TorrentRemove(ids, delete_local_data) => {
@@ -113,9 +563,321 @@ impl Serialize for Method {
                 )?;
                 map.end()
             }
-            Method::SessionStats => {
-                let mut map = serializer.serialize_map(Some(1))?;
-                map.serialize_entry("method", "session-stats")?;
+            Method::TorrentAdd(params) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("method", "torrent-add")?;
+                map.serialize_entry("arguments", &params)?;
+                map.end()
+            }
+            Method::SessionStats => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("method", "session-stats")?;
+                map.end()
+            }
+            Method::SessionGet => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("method", "session-get")?;
+                map.serialize_entry(
+                    "arguments",
+                    &serde_json::json!({ "fields": ["rpc-version", "rpc-version-minimum", "version"] }),
+                )?;
+                map.end()
+            }
+        }
+    }
+}
+
+/// Arguments of the methods that are not given as parameters structure
+#[derive(Deserialize, Default)]
+struct MethodArguments {
+    ids: Option<TorrentId>,
+    #[serde(default)]
+    fields: Vec<TorrentField>,
+    format: Option<TorrentGetFormat>,
+    #[serde(rename = "delete-local-data", default)]
+    delete_local_data: bool,
+}
+
+/// Ids of a list of torrent ids, which is sent flattened
+fn torrent_id_list(ids: TorrentId) -> Vec<TorrentId> {
+    match ids {
+        TorrentId::List(ids) => ids.into_iter().map(TorrentId::Id).collect(),
+        id => vec![id],
+    }
+}
+
+/// Deserialize a method of the legacy dialect
+impl<'de> Deserialize<'de> for Method {
+    fn deserialize<D>(deserializer: D) -> Result<Method, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct RawMethod {
+            method: String,
+            arguments: Option<serde_json::Value>,
+        }
+
+        fn from_arguments<T, E>(arguments: Option<serde_json::Value>) -> Result<T, E>
+        where
+            T: de::DeserializeOwned + Default,
+            E: de::Error,
+        {
+            arguments
+                .map(serde_json::from_value)
+                .transpose()
+                .map(Option::unwrap_or_default)
+                .map_err(E::custom)
+        }
+
+        let RawMethod { method, arguments } = RawMethod::deserialize(deserializer)?;
+        let ids = |arguments| from_arguments::<MethodArguments, D::Error>(arguments).map(|a| a.ids);
+        match method.as_str() {
+            "torrent-start" => Ok(Method::TorrentStart(ids(arguments)?)),
+            "torrent-start-now" => Ok(Method::TorrentStartNow(ids(arguments)?)),
+            "torrent-stop" => Ok(Method::TorrentStop(ids(arguments)?)),
+            "torrent-verify" => Ok(Method::TorrentVerify(ids(arguments)?)),
+            "torrent-reannounce" => Ok(Method::TorrentReannounce(ids(arguments)?)),
+            "torrent-set" => Ok(Method::TorrentSet(Box::new(from_arguments(arguments)?))),
+            "torrent-get" => {
+                let args: MethodArguments = from_arguments(arguments)?;
+                Ok(Method::TorrentGet(
+                    args.fields,
+                    args.ids.map(torrent_id_list),
+                    args.format,
+                ))
+            }
+            "torrent-remove" => {
+                let args: MethodArguments = from_arguments(arguments)?;
+                Ok(Method::TorrentRemove(
+                    args.ids.map(torrent_id_list).unwrap_or_default(),
+                    args.delete_local_data,
+                ))
+            }
+            "torrent-add" => Ok(Method::TorrentAdd(Box::new(from_arguments(arguments)?))),
+            "session-stats" => Ok(Method::SessionStats),
+            "session-get" => Ok(Method::SessionGet),
+            _ => Err(de::Error::unknown_variant(
+                &method,
+                &[
+                    "torrent-start",
+                    "torrent-start-now",
+                    "torrent-stop",
+                    "torrent-verify",
+                    "torrent-reannounce",
+                    "torrent-set",
+                    "torrent-get",
+                    "torrent-remove",
+                    "torrent-add",
+                    "session-stats",
+                    "session-get",
+                ],
+            )),
+        }
+    }
+}
+
+/// First `rpc-version` that speak JSON-RPC 2.0 with snake_case keys (Transmission 4.1)
+pub const JSON_RPC_MIN_VERSION: u64 = 18;
+
+/// Dialect of the Transmission RPC
+#[derive(Deserialize, Serialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcDialect {
+    /// Historical dialect with `tag`, `arguments` and a `result` string. Keys are in camelCase or kebab-case
+    #[default]
+    Legacy,
+    /// JSON-RPC 2.0 with `id`, `params`, `result` and `error` objects. Keys are in snake_case
+    JsonRpc,
+}
+
+impl RpcDialect {
+    /// Getter of the dialect to use with the `rpc-version` returned by `session-get`
+    pub fn from_rpc_version(rpc_version: u64) -> RpcDialect {
+        if rpc_version >= JSON_RPC_MIN_VERSION {
+            RpcDialect::JsonRpc
+        } else {
+            RpcDialect::Legacy
+        }
+    }
+}
+
+impl fmt::Display for RpcDialect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcDialect::Legacy => write!(f, "legacy"),
+            RpcDialect::JsonRpc => write!(f, "JSON-RPC 2.0"),
+        }
+    }
+}
+
+/// Legacy keys that are not in camelCase, by their snake_case name
+const LEGACY_KEYS: [(&str, &str); 19] = [
+    ("begin_piece", "begin_piece"),
+    ("bytes_to_client", "bytes_to_client"),
+    ("bytes_to_peer", "bytes_to_peer"),
+    ("cumulative_stats", "cumulative-stats"),
+    ("current_stats", "current-stats"),
+    ("downloader_count", "downloader_count"),
+    ("end_piece", "end_piece"),
+    ("file_count", "file-count"),
+    ("is_utp", "isUTP"),
+    ("peer_id", "peer_id"),
+    ("peer_limit", "peer-limit"),
+    ("primary_mime_type", "primary-mime-type"),
+    ("rpc_version", "rpc-version"),
+    ("rpc_version_minimum", "rpc-version-minimum"),
+    ("rpc_version_semver", "rpc-version-semver"),
+    ("sequential_download", "sequential_download"),
+    (
+        "sequential_download_from_piece",
+        "sequential_download_from_piece",
+    ),
+    ("torrent_added", "torrent-added"),
+    ("torrent_duplicate", "torrent-duplicate"),
+];
+
+/// Convert a snake_case key of the JSON-RPC dialect to its legacy name
+pub fn legacy_key(key: &str) -> Cow<'_, str> {
+    if let Some((_, legacy)) = LEGACY_KEYS.iter().find(|(snake, _)| *snake == key) {
+        Cow::Borrowed(legacy)
+    } else if key.contains('_') {
+        let mut legacy = String::with_capacity(key.len());
+        let mut upper = false;
+        for c in key.chars() {
+            if c == '_' {
+                upper = true;
+            } else if upper {
+                legacy.push(c.to_ascii_uppercase());
+                upper = false;
+            } else {
+                legacy.push(c);
+            }
+        }
+        Cow::Owned(legacy)
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+/// Convert a legacy key (camelCase or kebab-case) to snake_case for the JSON-RPC dialect
+pub fn snake_key(key: &str) -> Cow<'_, str> {
+    let mut snake = String::with_capacity(key.len() + 4);
+    let mut previous_lower = false;
+    for c in key.chars() {
+        if c == '-' {
+            snake.push('_');
+            previous_lower = false;
+        } else if c.is_ascii_uppercase() {
+            if previous_lower {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+            previous_lower = false;
+        } else {
+            snake.push(c);
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+    }
+    Cow::Owned(snake)
+}
+
+/// Rename recursively all the object keys of a JSON value
+pub(crate) fn map_keys<F>(value: &mut serde_json::Value, rename: &F)
+where
+    F: for<'k> Fn(&'k str) -> Cow<'k, str>,
+{
+    match value {
+        serde_json::Value::Object(map) => {
+            *map = std::mem::take(map)
+                .into_iter()
+                .map(|(key, mut value)| {
+                    map_keys(&mut value, rename);
+                    (rename(&key).into_owned(), value)
+                })
+                .collect();
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                map_keys(value, rename);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Request to send to Transmission in a dialect
+#[derive(Debug)]
+pub struct Request<'a> {
+    method: &'a Method,
+    dialect: RpcDialect,
+    id: Option<u64>,
+}
+
+impl<'a> Request<'a> {
+    /// Create a request of the method in the dialect.
+    /// The `id` is sent as `tag` for the legacy dialect. A JSON-RPC request without `id` is a notification, so Transmission won't respond to it
+    pub fn new(method: &'a Method, dialect: RpcDialect, id: Option<u64>) -> Request<'a> {
+        Request {
+            method,
+            dialect,
+            id,
+        }
+    }
+}
+
+impl Serialize for Request<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.dialect == RpcDialect::Legacy && self.id.is_none() {
+            return self.method.serialize(serializer);
+        }
+
+        let mut request = serde_json::to_value(self.method).map_err(ser::Error::custom)?;
+        let serde_json::Value::Object(request) = &mut request else {
+            return Err(ser::Error::custom("transmission method must be an object"));
+        };
+
+        match self.dialect {
+            RpcDialect::Legacy => {
+                if let Some(id) = self.id {
+                    request.insert("tag".to_string(), id.into());
+                }
+                request.serialize(serializer)
+            }
+            RpcDialect::JsonRpc => {
+                let method = request
+                    .remove("method")
+                    .and_then(|m| m.as_str().map(|m| m.replace('-', "_")));
+                let params = request.remove("arguments").map(|mut params| {
+                    map_keys(&mut params, &snake_key);
+                    if let serde_json::Value::Object(params) = &mut params {
+                        // Field names and `recently-active` are values that must be renamed too
+                        if let Some(serde_json::Value::Array(fields)) = params.get_mut("fields") {
+                            for field in fields {
+                                if let serde_json::Value::String(name) = field {
+                                    *name = snake_key(name).into_owned();
+                                }
+                            }
+                        }
+                        if let Some(serde_json::Value::String(ids)) = params.get_mut("ids") {
+                            *ids = snake_key(ids).into_owned();
+                        }
+                    }
+                    params
+                });
+
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("jsonrpc", "2.0")?;
+                map.serialize_entry("method", &method)?;
+                if let Some(params) = params {
+                    map.serialize_entry("params", &params)?;
+                }
+                if let Some(id) = self.id {
+                    map.serialize_entry("id", &id)?;
+                }
                 map.end()
             }
         }
@@ -156,13 +918,46 @@ impl<'a> From<&'a TorrentId> for ArgumentsId<'a> {
 #[derive(Serialize)]
 struct ArgumentsTorrentGet<'a> {
     fields: &'a Vec<TorrentField>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_torrent_get_ids"
+    )]
     ids: &'a Option<Vec<TorrentId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<TorrentGetFormat>,
+}
+
+/// Format of the `torrent-get` response
+#[derive(Deserialize, Serialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TorrentGetFormat {
+    /// Torrents are returned as an array of objects
+    #[default]
+    Objects,
+    /// Torrents are returned as an array of arrays. The first one contain the keys, and the others the values of each torrent
+    Table,
+}
+
+/// `recently-active` must be sent as a single string and not in a list
+fn serialize_torrent_get_ids<S>(
+    ids: &&Option<Vec<TorrentId>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match ids {
+        Some(ids) if ids.len() == 1 && ids[0] == TorrentId::RecentlyActive => {
+            ids[0].serialize(serializer)
+        }
+        ids => ids.serialize(serializer),
+    }
 }
 
 #[derive(Serialize)]
 struct ArgumentsTorrentRemove<'a> {
     ids: &'a Vec<TorrentId>,
+    #[serde(rename = "delete-local-data")]
     delete_local_data: bool,
 }
 
@@ -227,6 +1022,22 @@ pub enum StatErrType {
     Unknown,
 }
 
+impl Serialize for StatErrType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            StatErrType::Ok => 0i8,
+            StatErrType::TrackerWarning => 1,
+            StatErrType::TrackerError => 2,
+            StatErrType::LocalError => 3,
+            StatErrType::Unknown => -1,
+        }
+        .serialize(serializer)
+    }
+}
+
 impl<'de> Deserialize<'de> for StatErrType {
     fn deserialize<D>(deserializer: D) -> Result<StatErrType, D::Error>
     where
@@ -264,31 +1075,36 @@ pub enum RatioLimit {
     Unlimited = 2,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct File {
     #[serde(rename = "bytesCompleted")]
     pub bytes_completed: u64,
     pub length: u64,
     pub name: String,
-    pub begin_piece: u32,
-    pub end_piece: u32,
+    /// First piece of the file, not given by Transmission 3
+    pub begin_piece: Option<u32>,
+    /// Last piece of the file (excluded), not given by Transmission 3
+    pub end_piece: Option<u32>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct FileStat {
     #[serde(rename = "bytesCompleted")]
-    pub bytes_completed: f64,
+    pub bytes_completed: u64,
     pub wanted: bool,
     pub priority: Priority,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Peer {
     pub address: String,
-    #[serde(rename = "bytes_to_client")]
+    /// Bytes received from the peer, not given by older Transmission versions
+    #[serde(rename = "bytes_to_client", default)]
     pub bytes_to_client: u64,
-    #[serde(rename = "bytes_to_peer")]
+    /// Bytes sent to the peer, not given by older Transmission versions
+    #[serde(rename = "bytes_to_peer", default)]
     pub bytes_to_peer: u64,
     pub client_name: String,
     pub client_is_choked: bool,
@@ -298,10 +1114,12 @@ pub struct Peer {
     pub is_encrypted: bool,
     pub is_incoming: bool,
     pub is_uploading_to: bool,
+    #[serde(rename = "isUTP")]
     pub is_utp: bool,
     pub peer_is_choked: bool,
     pub peer_is_interested: bool,
-    #[serde(rename = "peer_id")]
+    /// Peer id, not given by older Transmission versions
+    #[serde(rename = "peer_id", default)]
     pub peer_id: String,
     pub port: u16,
     pub progress: f64,
@@ -309,19 +1127,19 @@ pub struct Peer {
     pub rate_to_peer: u32,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PeerFrom {
     pub from_cache: u64,
     pub from_dht: u64,
     pub from_incoming: u64,
     pub from_lpd: u64,
-    pub from_ltpep: u64,
+    pub from_ltep: u64,
     pub from_pex: u64,
     pub from_tracker: u64,
 }
 
-#[derive(Deserialize_repr, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Status {
     /// Torrent is stopped
@@ -370,7 +1188,7 @@ impl fmt::Display for Status {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Tracker {
     /// full announce URL
     pub announce: String,
@@ -381,13 +1199,20 @@ pub struct Tracker {
     /// The tracker site's name. Uses the first label before the public suffix
     /// (https://publicsuffix.org/) in the announce URL's host.
     /// e.g. "https://www.example.co.uk/announce/"'s sitename is "example"
-    /// RFC 1034 says labels must be less than 64 chars
+    /// RFC 1034 says labels must be less than 64 chars.
+    /// Not given by Transmission 3
+    #[serde(default)]
     pub sitename: String,
     /// which tier this tracker is in
     pub tier: i64,
 }
 
-#[derive(Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq)]
+/// Default count of a tracker stat not given by Transmission
+fn unknown_count() -> i64 {
+    -1
+}
+
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum TrackerState {
     /// we won't (announce,scrape) this torrent to this tracker because
@@ -403,7 +1228,8 @@ pub enum TrackerState {
     Active = 3,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TrackerStat {
     /// full announce URL
@@ -413,7 +1239,7 @@ pub struct TrackerStat {
     /// number of times this torrent's been downloaded, or -1 if unknown
     pub download_count: i64,
     /// number of downloaders (BEP-21) the tracker knows of, or -1 if unknown
-    #[serde(rename = "downloader_count")]
+    #[serde(rename = "downloader_count", default = "unknown_count")]
     pub downloader_count: i64,
     /// true if we've announced to this tracker during this session
     #[serde(default)]
@@ -433,13 +1259,13 @@ pub struct TrackerStat {
     /// if hasAnnounced, the human-readable result of latest announce
     pub last_announce_result: Option<String>,
     /// if hasAnnounced, when the latest announce request was sent
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub last_announce_start_time: Option<DateTime<Utc>>,
     /// if hasAnnounced, whether or not the latest announce succeeded
     #[serde(default)]
     pub last_announce_succeeded: bool,
     /// if hasAnnounced, when the latest announce reply was received
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub last_announce_time: Option<DateTime<Utc>>,
     /// true if the latest announce request timed out
     #[serde(default)]
@@ -447,13 +1273,13 @@ pub struct TrackerStat {
     /// if hasScraped, the human-readable result of the latest scrape
     pub last_scrape_result: Option<String>,
     /// if hasScraped, when the latest scrape request was sent
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub last_scrape_start_time: Option<DateTime<Utc>>,
     /// if hasScraped, whether or not the latest scrape succeeded
     #[serde(default)]
     pub last_scrape_succeeded: bool,
     /// if hasScraped, when the latest scrape reply was received
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub last_scrape_time: Option<DateTime<Utc>>,
     /// true if the latest scrape request timed out
     #[serde(default)]
@@ -461,10 +1287,10 @@ pub struct TrackerStat {
     /// number of leechers the tracker knows of, or -1 if unknown
     pub leecher_count: i64,
     /// if announceState == TR_TRACKER_WAITING, time of next announce
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub next_announce_time: Option<DateTime<Utc>>,
     /// if scrapeState == TR_TRACKER_WAITING, time of next scrape
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub next_scrape_time: Option<DateTime<Utc>>,
     /// full scrape URL
     pub scrape: String,
@@ -475,14 +1301,16 @@ pub struct TrackerStat {
     /// The tracker site's name. Uses the first label before the public suffix
     /// (https://publicsuffix.org/) in the announce URL's host.
     /// e.g. "https://www.example.co.uk/announce/"'s sitename is "example"
-    /// RFC 1034 says labels must be less than 64 chars
+    /// RFC 1034 says labels must be less than 64 chars.
+    /// Not given by Transmission 3
+    #[serde(default)]
     pub sitename: String,
     /// which tier this tracker is in
     pub tier: i64,
 }
 
 #[skip_serializing_none]
-#[derive(Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TorrentSetParams {
     /// this torrent's bandwidth priority
@@ -530,8 +1358,10 @@ pub struct TorrentSetParams {
     /// which ratio to use
     pub seed_ratio_mode: Option<RatioLimit>,
     /// download torrent pieces sequentially
+    #[serde(rename = "sequential_download")]
     pub sequential_download: Option<bool>,
     /// download from a specific piece when sequential download is enabled
+    #[serde(rename = "sequential_download_from_piece")]
     pub sequential_download_from_piece: Option<u32>,
     /// DEPRECATED use tracker_list instead
     pub tracker_add: Option<Vec<String>>,
@@ -547,7 +1377,24 @@ pub struct TorrentSetParams {
     pub upload_limited: Option<bool>,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+/// Parameters of `torrent-add`. Either `filename` or `metainfo` must be set
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct TorrentAddParams {
+    /// pointer to the content directory
+    pub download_dir: Option<String>,
+    /// filename or URL of the .torrent file, or a magnet link
+    pub filename: Option<String>,
+    /// array of string labels
+    pub labels: Option<Vec<String>>,
+    /// base64-encoded .torrent content
+    pub metainfo: Option<String>,
+    /// if true, don't start the torrent
+    pub paused: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum TorrentField {
     /// The last time we uploaded or downloaded piece data on this torrent
@@ -604,6 +1451,7 @@ pub enum TorrentField {
     PieceCount,
     PieceSize,
     Priorities,
+    #[serde(rename = "primary-mime-type")]
     PrimaryMimeType,
     QueuePosition,
     RateDownload,
@@ -615,7 +1463,9 @@ pub enum TorrentField {
     SeedIdleMode,
     SeedRatioLimit,
     SeedRatioMode,
+    #[serde(rename = "sequential_download")]
     SequentialDownload,
+    #[serde(rename = "sequential_download_from_piece")]
     SequentialDownloadFromPiece,
     SizeWhenDone,
     StartDate,
@@ -634,17 +1484,18 @@ pub enum TorrentField {
     WebseedsSendingToUs,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TorrentsArguments {
     /// The last time we uploaded or downloaded piece data on this torrent
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub activity_date: Option<DateTime<Utc>>,
     /// When the torrent was first added
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub added_date: Option<DateTime<Utc>>,
     /// An array of pieceCount numbers representing the number of connected peers that have each piece, or -1 if we already have the piece ourselves
-    pub availability: Option<Vec<u32>>,
+    pub availability: Option<Vec<i64>>,
     /// Bandwidth priority of the torrent
     pub bandwidth_priority: Option<Priority>,
     /// An array of `tr_info.filecount` numbers. Each is the completed bytes for the corresponding file
@@ -657,12 +1508,12 @@ pub struct TorrentsArguments {
     /// Creator name of the torrent
     pub creator: Option<String>,
     /// Creation date of the torrent
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub date_created: Option<DateTime<Utc>>,
     /// Byte count of all the piece data we want and don't have yet, but that a connected peer does have. [0...leftUntilDone]
     pub desired_available: Option<u64>,
     /// When the torrent finished downloading
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub done_date: Option<DateTime<Utc>>,
     /// Torrent download folder
     pub download_dir: Option<String>,
@@ -677,7 +1528,7 @@ pub struct TorrentsArguments {
     /// The last time during this session that a rarely-changing field changed
     /// e.g. any `tr_torrent_metainfo` field (trackers, filenames, name) or download directory.
     /// RPC clients can monitor this to know when to reload fields that rarely change
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub edit_date: Option<DateTime<Utc>>,
     /// Defines what kind of text is in errorString
     pub error: Option<StatErrType>,
@@ -685,10 +1536,10 @@ pub struct TorrentsArguments {
     pub error_string: Option<String>,
     /// If downloading, estimated number of seconds left until the torrent is done.
     /// If seeding, estimated number of seconds left until seed ratio is reached
-    #[serde(deserialize_with = "deserialize_time", default)]
+    #[serde(with = "duration", default)]
     pub eta: Option<TimeDelta>,
     /// If seeding, number of seconds left until the idle time limit is reached
-    #[serde(deserialize_with = "deserialize_time", default)]
+    #[serde(with = "duration", default)]
     pub eta_idle: Option<TimeDelta>,
     /// Number of file in the torrent
     #[serde(rename = "file-count")]
@@ -724,7 +1575,7 @@ pub struct TorrentsArguments {
     pub left_until_done: Option<u64>,
     pub magnet_link: Option<String>,
     /// DEPRECATED don't use it, it never worked
-    pub manual_announce_time: Option<u64>,
+    pub manual_announce_time: Option<i64>,
     pub max_connected_peers: Option<u64>,
     pub metadata_percent_complete: Option<f64>,
     pub name: Option<String>,
@@ -743,7 +1594,7 @@ pub struct TorrentsArguments {
     /// How much has been downloaded of the files the user wants.
     /// This differs from percentComplete if the user wants only some of the torrent's files. Range is [0..1]
     pub percent_done: Option<f64>,
-    #[serde(deserialize_with = "deserialize_bitfield", default)]
+    #[serde(with = "bitfield", default)]
     pub pieces: Option<Vec<u8>>,
     pub piece_count: Option<u32>,
     pub piece_size: Option<u64>,
@@ -761,10 +1612,10 @@ pub struct TorrentsArguments {
     /// When it gets to 1, the verify process is done. Range is [0..1]
     pub recheck_progress: Option<f32>,
     /// Cumulative seconds the torrent's ever spent downloading
-    #[serde(deserialize_with = "deserialize_time", default)]
+    #[serde(with = "duration", default)]
     pub seconds_downloading: Option<TimeDelta>,
     /// Cumulative seconds the torrent's ever spent seeding
-    #[serde(deserialize_with = "deserialize_time", default)]
+    #[serde(with = "duration", default)]
     pub seconds_seeding: Option<TimeDelta>,
     pub seed_idle_limit: Option<i64>,
     pub seed_idle_mode: Option<IdleLimit>,
@@ -779,7 +1630,7 @@ pub struct TorrentsArguments {
     /// Byte count of all the piece data we'll have downloaded when we're done, whether or not we have it yet.
     /// If we only want some of the files, this may be less than `tr_torrent_view.total_size`. [0...tr_torrent_view.total_size]
     pub size_when_done: Option<u64>,
-    #[serde(deserialize_with = "deserialize_timestamp", default)]
+    #[serde(with = "timestamp", default)]
     pub start_date: Option<DateTime<Utc>>,
     pub status: Option<Status>,
     pub trackers: Option<Vec<Tracker>>,
@@ -795,13 +1646,42 @@ pub struct TorrentsArguments {
     pub upload_limited: bool,
     pub upload_ratio: Option<f64>,
     /// An array of tr_torrentFileCount() 0/1, 1 (true) if the corresponding file is to be downloaded. (Source: tr_file_view)
+    #[serde(with = "wanted", default)]
     pub wanted: Option<Vec<bool>>,
     pub webseeds: Option<Vec<String>>,
     /// Number of webseeds that are sending data to us
     pub webseeds_sending_to_us: Option<u16>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl TorrentsArguments {
+    /// Build torrent arguments from a row of a table format response, with the keys of each value
+    pub fn from_table_row<K>(
+        keys: &[K],
+        row: Vec<serde_json::Value>,
+    ) -> Result<TorrentsArguments, serde_json::Error>
+    where
+        K: AsRef<str>,
+    {
+        serde_json::from_value(serde_json::Value::Object(
+            keys.iter()
+                .map(|k| k.as_ref().to_string())
+                .zip(row)
+                .collect(),
+        ))
+    }
+
+    /// Build torrent arguments from a row of a table format response, with the requested fields order
+    pub fn from_table_fields(
+        fields: &[TorrentField],
+        row: Vec<serde_json::Value>,
+    ) -> Result<TorrentsArguments, serde_json::Error> {
+        let keys: Vec<String> = serde_json::from_value(serde_json::to_value(fields)?)?;
+        Self::from_table_row(&keys, row)
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TorrentAdded {
     /// Hash value of the torrent
@@ -811,7 +1691,8 @@ pub struct TorrentAdded {
     pub name: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionStats {
     /// Uploaded bytes in this session
@@ -821,20 +1702,36 @@ pub struct SessionStats {
     /// Number of files added in this session
     pub files_added: u32,
     /// Cumulative seconds the torrent's ever spent downloading
-    #[serde(deserialize_with = "deserialize_time", default)]
+    #[serde(with = "duration", default)]
     pub seconds_active: Option<TimeDelta>,
     /// Number of sessions
     pub session_count: u64,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ResponseArguments {
     pub version: Option<String>,
-    #[serde(default)]
+    /// Version of the RPC, to select the dialect
+    #[serde(rename = "rpc-version")]
+    pub rpc_version: Option<u64>,
+    /// Minimum version of the RPC supported by the server
+    #[serde(rename = "rpc-version-minimum")]
+    pub rpc_version_minimum: Option<u64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_torrents",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub torrents: Vec<TorrentsArguments>,
+    /// Ids of torrents removed since the last `recently-active` request
+    pub removed: Option<Vec<Id>>,
     #[serde(rename = "torrent-added")]
     pub torrent_added: Option<TorrentAdded>,
+    /// Torrent already present when adding it
+    #[serde(rename = "torrent-duplicate")]
+    pub torrent_duplicate: Option<TorrentAdded>,
 
     pub active_torrent_count: Option<u32>,
     pub download_speed: Option<u64>,
@@ -847,17 +1744,265 @@ pub struct ResponseArguments {
     pub current_stats: Option<SessionStats>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// Error object of a JSON-RPC 2.0 response
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct RpcError {
+    /// Error code
+    pub code: i64,
+    /// Short description of the error
+    pub message: String,
+    /// Additional information about the error
+    pub data: Option<serde_json::Value>,
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)?;
+        if let Some(data) = &self.data {
+            write!(f, ": {data}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Response of Transmission, in the legacy or JSON-RPC 2.0 dialect.
+/// It's serialized in the legacy dialect
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone)]
 pub struct Response {
     pub arguments: ResponseArguments,
+    /// `success` or an error string
     pub result: String,
+    /// `tag` of the legacy dialect, or `id` of JSON-RPC
     pub tag: Option<u64>,
+    /// Error of a JSON-RPC response
+    pub error: Option<RpcError>,
+}
+
+impl<'de> Deserialize<'de> for Response {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut torrents = Vec::new();
+        let mut response = de::DeserializeSeed::deserialize(
+            ResponseSeed(&mut |torrent| torrents.push(torrent)),
+            deserializer,
+        )?;
+        response.arguments.torrents = torrents;
+        Ok(response)
+    }
+}
+
+impl Response {
+    /// Indicate if the request succeed
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && self.result == "success"
+    }
+
+    /// Deserialize a response from a reader, giving torrents one by one to the visitor instead of building the torrent list.
+    /// The torrent list of the returned response is always empty
+    pub fn from_reader_visit<R, V>(
+        reader: R,
+        visitor: &mut V,
+    ) -> Result<Response, serde_json::Error>
+    where
+        R: std::io::Read,
+        V: TorrentVisitor,
+    {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let response = de::DeserializeSeed::deserialize(ResponseSeed(visitor), &mut deserializer)?;
+        deserializer.end()?;
+        Ok(response)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::RefCell, collections::HashSet};
+
+    use serde::forward_to_deserialize_any;
+    use serde_json::{Value, json};
+
     use super::*;
 
+    /// Check that every value of the fixture is serialized back unchanged.
+    /// Floats may lose their `.0` and `wanted` flags are normalized from `0`/`1` to booleans
+    fn assert_kept(expected: &Value, actual: &Value, path: &str) {
+        match (expected, actual) {
+            (Value::Object(expected), Value::Object(actual)) => {
+                for (key, value) in expected {
+                    let path = format!("{path}.{key}");
+                    assert_kept(
+                        value,
+                        actual.get(key).unwrap_or_else(|| panic!("{path} is lost")),
+                        &path,
+                    );
+                }
+            }
+            (Value::Array(expected), Value::Array(actual)) => {
+                assert_eq!(expected.len(), actual.len(), "{path}");
+                for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                    assert_kept(expected, actual, &format!("{path}[{i}]"));
+                }
+            }
+            (Value::Number(expected), Value::Number(actual)) => {
+                assert_eq!(expected.as_f64(), actual.as_f64(), "{path}")
+            }
+            (Value::Number(expected), Value::Bool(actual)) => {
+                assert_eq!(expected.as_u64(), Some(*actual as u64), "{path}")
+            }
+            (expected, actual) => assert_eq!(expected, actual, "{path}"),
+        }
+    }
+
+    /// Deserializer of a JSON value, that record the path of every key ignored by the deserialized struct
+    struct KeyTracker<'de, 'k> {
+        value: &'de Value,
+        path: String,
+        ignored: &'k RefCell<Vec<String>>,
+    }
+
+    impl<'de> Deserializer<'de> for KeyTracker<'de, '_> {
+        type Error = serde_json::Error;
+
+        fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self.value {
+                Value::Null => visitor.visit_unit(),
+                Value::Bool(b) => visitor.visit_bool(*b),
+                Value::Number(n) => {
+                    if let Some(n) = n.as_u64() {
+                        visitor.visit_u64(n)
+                    } else if let Some(n) = n.as_i64() {
+                        visitor.visit_i64(n)
+                    } else {
+                        visitor.visit_f64(n.as_f64().unwrap_or_default())
+                    }
+                }
+                Value::String(s) => visitor.visit_borrowed_str(s),
+                Value::Array(values) => visitor.visit_seq(KeyTrackerSeq {
+                    values: values.iter().enumerate(),
+                    path: self.path,
+                    ignored: self.ignored,
+                }),
+                Value::Object(map) => visitor.visit_map(KeyTrackerMap {
+                    entries: map.iter(),
+                    value: None,
+                    path: self.path,
+                    ignored: self.ignored,
+                }),
+            }
+        }
+
+        fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self.value {
+                Value::Null => visitor.visit_none(),
+                _ => visitor.visit_some(self),
+            }
+        }
+
+        fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.ignored.borrow_mut().push(self.path);
+            visitor.visit_unit()
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier
+        }
+    }
+
+    struct KeyTrackerSeq<'de, 'k> {
+        values: std::iter::Enumerate<std::slice::Iter<'de, Value>>,
+        path: String,
+        ignored: &'k RefCell<Vec<String>>,
+    }
+
+    impl<'de> de::SeqAccess<'de> for KeyTrackerSeq<'de, '_> {
+        type Error = serde_json::Error;
+
+        fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+        where
+            T: de::DeserializeSeed<'de>,
+        {
+            self.values
+                .next()
+                .map(|(i, value)| {
+                    seed.deserialize(KeyTracker {
+                        value,
+                        path: format!("{}[{i}]", self.path),
+                        ignored: self.ignored,
+                    })
+                })
+                .transpose()
+        }
+    }
+
+    struct KeyTrackerMap<'de, 'k> {
+        entries: serde_json::map::Iter<'de>,
+        value: Option<(&'de String, &'de Value)>,
+        path: String,
+        ignored: &'k RefCell<Vec<String>>,
+    }
+
+    impl<'de> de::MapAccess<'de> for KeyTrackerMap<'de, '_> {
+        type Error = serde_json::Error;
+
+        fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+        where
+            K: de::DeserializeSeed<'de>,
+        {
+            self.value = self.entries.next();
+            self.value
+                .map(|(key, _)| {
+                    seed.deserialize(de::value::BorrowedStrDeserializer::new(key.as_str()))
+                })
+                .transpose()
+        }
+
+        fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::DeserializeSeed<'de>,
+        {
+            let (key, value) = self
+                .value
+                .take()
+                .ok_or_else(|| de::Error::custom("value without a key"))?;
+            seed.deserialize(KeyTracker {
+                value,
+                path: format!("{}.{key}", self.path),
+                ignored: self.ignored,
+            })
+        }
+    }
+
+    /// Deserialize a fixture value, and return the keys unknown by the struct.
+    /// Missing keys of mandatory fields are reported as errors
+    fn conformance<'de, T>(value: &'de Value, path: &str) -> (T, Vec<String>)
+    where
+        T: Deserialize<'de>,
+    {
+        let ignored = RefCell::new(Vec::new());
+        let deserialized = T::deserialize(KeyTracker {
+            value,
+            path: path.to_string(),
+            ignored: &ignored,
+        })
+        .unwrap_or_else(|e| panic!("{path}: {e}"));
+        (deserialized, ignored.into_inner())
+    }
+
     #[test]
     fn torrent_id() {
         let id = TorrentId::from(7);
@@ -910,6 +2055,7 @@ mod tests {
                 TorrentField::TotalSize,
             ],
             Some(vec![7.into(), 10.into()]),
+            None,
         );
         assert_eq!(
             Some(
@@ -918,6 +2064,18 @@ mod tests {
             serde_json::to_string(&request).ok().as_deref()
         );
 
+        let request = Method::TorrentGet(
+            vec![TorrentField::Id, TorrentField::Status],
+            Some(vec![TorrentId::RecentlyActive]),
+            None,
+        );
+        assert_eq!(
+            Some(
+                "{\"method\":\"torrent-get\",\"arguments\":{\"fields\":[\"id\",\"status\"],\"ids\":\"recently-active\"}}"
+            ),
+            serde_json::to_string(&request).ok().as_deref()
+        );
+
         let response = r#"
             {
                 "arguments": {
@@ -932,7 +2090,8 @@ mod tests {
                             "name": "Ubuntu x86_64 DVD",
                             "totalSize": 9923890123
                         }
-                    ]
+                    ],
+                    "removed": [3, 4]
                 },
                 "result": "success",
                 "tag": 39693
@@ -941,6 +2100,7 @@ mod tests {
         assert_eq!(Some(39693), v.tag);
         assert_eq!("success", v.result);
         assert_eq!(2, v.arguments.torrents.len());
+        assert_eq!(Some(vec![Id::Id(3), Id::Id(4)]), v.arguments.removed);
         for torrent in v.arguments.torrents {
             match torrent.id {
                 Some(TorrentId::Id(Id::Id(7))) => {
@@ -955,4 +2115,333 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn torrent_table() {
+        let request = Method::TorrentGet(
+            vec![
+                TorrentField::Id,
+                TorrentField::Name,
+                TorrentField::TotalSize,
+            ],
+            None,
+            Some(TorrentGetFormat::Table),
+        );
+        assert_eq!(
+            Some(
+                "{\"method\":\"torrent-get\",\"arguments\":{\"fields\":[\"id\",\"name\",\"totalSize\"],\"format\":\"table\"}}"
+            ),
+            serde_json::to_string(&request).ok().as_deref()
+        );
+
+        let response = r#"
+            {
+                "arguments": {
+                    "torrents": [
+                        ["id", "name", "totalSize"],
+                        [10, "Fedora x86_64 DVD", 34983493932],
+                        [7, "Ubuntu x86_64 DVD", 9923890123]
+                    ]
+                },
+                "result": "success"
+            }"#;
+        let v: Response = serde_json::from_str(response).unwrap();
+        assert_eq!(2, v.arguments.torrents.len());
+        assert_eq!(Some(TorrentId::from(10)), v.arguments.torrents[0].id);
+        assert_eq!(
+            Some("Ubuntu x86_64 DVD"),
+            v.arguments.torrents[1].name.as_deref()
+        );
+        assert_eq!(Some(9923890123), v.arguments.torrents[1].total_size);
+
+        let torrent = TorrentsArguments::from_table_fields(
+            &[TorrentField::Name, TorrentField::PeerLimit],
+            vec!["Debian".into(), 50.into()],
+        )
+        .unwrap();
+        assert_eq!(Some("Debian"), torrent.name.as_deref());
+        assert_eq!(Some(50), torrent.peer_limit);
+    }
+
+    #[test]
+    fn json_rpc() {
+        assert_eq!(RpcDialect::Legacy, RpcDialect::from_rpc_version(17));
+        assert_eq!(RpcDialect::JsonRpc, RpcDialect::from_rpc_version(18));
+        assert_eq!("peer-limit", legacy_key("peer_limit"));
+        assert_eq!("percentDone", legacy_key("percent_done"));
+        assert_eq!("percentDone", legacy_key("percentDone"));
+        assert_eq!("percent_done", snake_key("percentDone"));
+        assert_eq!("files_wanted", snake_key("files-wanted"));
+        assert_eq!("is_utp", snake_key("isUTP"));
+        assert_eq!("isUTP", legacy_key("is_utp"));
+
+        let method = Method::TorrentGet(
+            vec![
+                TorrentField::Id,
+                TorrentField::PercentDone,
+                TorrentField::PeerLimit,
+            ],
+            Some(vec![TorrentId::RecentlyActive]),
+            None,
+        );
+        assert_eq!(
+            Some(
+                "{\"arguments\":{\"fields\":[\"id\",\"percentDone\",\"peer-limit\"],\"ids\":\"recently-active\"},\"method\":\"torrent-get\",\"tag\":4}"
+            ),
+            serde_json::to_string(&Request::new(&method, RpcDialect::Legacy, Some(4)))
+                .ok()
+                .as_deref()
+        );
+        assert_eq!(
+            Some(
+                "{\"jsonrpc\":\"2.0\",\"method\":\"torrent_get\",\"params\":{\"fields\":[\"id\",\"percent_done\",\"peer_limit\"],\"ids\":\"recently_active\"},\"id\":4}"
+            ),
+            serde_json::to_string(&Request::new(&method, RpcDialect::JsonRpc, Some(4)))
+                .ok()
+                .as_deref()
+        );
+
+        let method = Method::TorrentSet(Box::new(TorrentSetParams {
+            ids: Some(vec![TorrentId::from(1)]),
+            files_wanted: Some(vec![0]),
+            upload_limited: Some(true),
+            ..Default::default()
+        }));
+        assert_eq!(
+            Some(
+                "{\"jsonrpc\":\"2.0\",\"method\":\"torrent_set\",\"params\":{\"files_wanted\":[0],\"ids\":[1],\"upload_limited\":true},\"id\":5}"
+            ),
+            serde_json::to_string(&Request::new(&method, RpcDialect::JsonRpc, Some(5)))
+                .ok()
+                .as_deref()
+        );
+
+        let response = r#"
+            {
+                "jsonrpc": "2.0",
+                "result": {
+                    "torrents": [
+                        {
+                            "id": 1,
+                            "percent_done": 0.5,
+                            "peer_limit": 50,
+                            "primary_mime_type": "video/x-matroska",
+                            "file_stats": [{ "bytes_completed": 1024, "wanted": true, "priority": 0 }]
+                        }
+                    ],
+                    "removed": [2]
+                },
+                "id": 4
+            }"#;
+        let v: Response = serde_json::from_str(response).unwrap();
+        assert!(v.is_success());
+        assert_eq!(Some(4), v.tag);
+        assert_eq!(Some(vec![Id::Id(2)]), v.arguments.removed);
+        assert_eq!(Some(0.5), v.arguments.torrents[0].percent_done);
+        assert_eq!(Some(50), v.arguments.torrents[0].peer_limit);
+        assert_eq!(
+            Some("video/x-matroska"),
+            v.arguments.torrents[0].primary_mime_type.as_deref()
+        );
+        assert_eq!(
+            1,
+            v.arguments.torrents[0].file_stats.as_ref().unwrap().len()
+        );
+
+        let response =
+            r#"{"jsonrpc":"2.0","result":{"torrents":[["id","peer_limit"],[1,50]]},"id":6}"#;
+        let v: Response = serde_json::from_str(response).unwrap();
+        assert_eq!(Some(50), v.arguments.torrents[0].peer_limit);
+
+        let response =
+            r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":7}"#;
+        let v: Response = serde_json::from_str(response).unwrap();
+        assert!(!v.is_success());
+        assert_eq!("Method not found", v.result);
+        assert_eq!(Some(-32601), v.error.map(|e| e.code));
+
+        let response = r#"{"arguments":{"rpc-version":18,"rpc-version-minimum":14,"version":"4.1.0"},"result":"success"}"#;
+        let v: Response = serde_json::from_str(response).unwrap();
+        assert!(v.is_success());
+        assert_eq!(Some(18), v.arguments.rpc_version);
+    }
+
+    #[test]
+    fn torrent_visitor() {
+        for response in [
+            r#"{"arguments":{"torrents":[{"id":10,"name":"Fedora"},{"id":7,"name":"Ubuntu"}],"removed":[3]},"result":"success","tag":5}"#,
+            r#"{"result":"success","arguments":{"removed":[3],"torrents":[["id","name"],[10,"Fedora"],[7,"Ubuntu"]]}}"#,
+        ] {
+            let mut names = Vec::new();
+            let v =
+                Response::from_reader_visit(response.as_bytes(), &mut |t: TorrentsArguments| {
+                    names.push(t.name.unwrap_or_default())
+                })
+                .unwrap();
+            assert_eq!(vec!["Fedora", "Ubuntu"], names);
+            assert_eq!("success", v.result);
+            assert!(v.arguments.torrents.is_empty());
+            assert_eq!(Some(vec![Id::Id(3)]), v.arguments.removed);
+        }
+    }
+
+    #[test]
+    fn golden_fixtures() {
+        let mut torrent_fields = HashSet::new();
+        for (version, torrent_get, session_stats) in [
+            (
+                "3.00",
+                include_str!("../../tests/fixtures/transmission/3.00-torrent-get.json"),
+                include_str!("../../tests/fixtures/transmission/3.00-session-stats.json"),
+            ),
+            (
+                "4.0.6",
+                include_str!("../../tests/fixtures/transmission/4.0.6-torrent-get.json"),
+                include_str!("../../tests/fixtures/transmission/4.0.6-session-stats.json"),
+            ),
+            (
+                "4.1.0",
+                include_str!("../../tests/fixtures/transmission/4.1.0-torrent-get.json"),
+                include_str!("../../tests/fixtures/transmission/4.1.0-session-stats.json"),
+            ),
+        ] {
+            for fixture in [torrent_get, session_stats] {
+                let response: Response = serde_json::from_str(fixture)
+                    .unwrap_or_else(|e| panic!("Transmission {version}: {e}"));
+                assert!(response.is_success(), "Transmission {version}");
+
+                // Arguments are checked with their legacy keys, like they are decoded for the JSON-RPC dialect
+                let mut fixture: Value = serde_json::from_str(fixture).unwrap();
+                let mut arguments = if let Some(arguments) = fixture.get_mut("arguments") {
+                    arguments.take()
+                } else {
+                    let mut arguments = fixture["result"].take();
+                    map_keys(&mut arguments, &legacy_key);
+                    arguments
+                };
+
+                let torrents = arguments
+                    .as_object_mut()
+                    .and_then(|a| a.remove("torrents"))
+                    .unwrap_or_default();
+                let (_, unknown_keys) = conformance::<ResponseArguments>(&arguments, version);
+                assert_eq!(Vec::<String>::new(), unknown_keys, "unknown keys");
+
+                for (i, torrent) in torrents.as_array().into_iter().flatten().enumerate() {
+                    let path = format!("{version}.torrents[{i}]");
+                    let (torrent_arguments, unknown_keys) =
+                        conformance::<TorrentsArguments>(torrent, &path);
+                    assert_eq!(Vec::<String>::new(), unknown_keys, "unknown keys");
+                    assert!(torrent_arguments.id.is_some(), "{path}");
+
+                    // Every key of a torrent must be a field that can be requested
+                    for key in torrent.as_object().into_iter().flat_map(|t| t.keys()) {
+                        match serde_json::from_value::<TorrentField>(Value::from(key.as_str())) {
+                            Ok(field) => {
+                                torrent_fields.insert(field);
+                            }
+                            Err(_) => assert_eq!(
+                                "manualAnnounceTime", key,
+                                "{path}: key without torrent field"
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+
+        // Fixtures cover all the 78 torrent fields
+        assert_eq!(78, torrent_fields.len());
+    }
+
+    #[test]
+    fn round_trip() {
+        for fixture in [
+            include_str!("../../tests/fixtures/transmission/3.00-torrent-get.json"),
+            include_str!("../../tests/fixtures/transmission/3.00-session-stats.json"),
+            include_str!("../../tests/fixtures/transmission/4.0.6-torrent-get.json"),
+            include_str!("../../tests/fixtures/transmission/4.0.6-session-stats.json"),
+            include_str!("../../tests/fixtures/transmission/4.1.0-torrent-get.json"),
+            include_str!("../../tests/fixtures/transmission/4.1.0-session-stats.json"),
+        ] {
+            let response: Response = serde_json::from_str(fixture).unwrap();
+            let json = serde_json::to_value(&response).unwrap();
+            // Responses are serialized in the legacy dialect, so only those fixtures can be compared as is
+            let fixture: Value = serde_json::from_str(fixture).unwrap();
+            if fixture.get("jsonrpc").is_none() {
+                assert_kept(&fixture, &json, "");
+            }
+            let round_trip: Response = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(json, serde_json::to_value(&round_trip).unwrap());
+            assert_eq!(response.tag, round_trip.tag);
+        }
+
+        // Sentinels are kept as they are, and fields that weren't received aren't serialized
+        let torrent =
+            json!({ "id": 7, "eta": -2, "etaIdle": -1, "editDate": 0, "secondsSeeding": 0 });
+        let json = serde_json::to_value(
+            serde_json::from_value::<TorrentsArguments>(torrent.clone()).unwrap(),
+        )
+        .unwrap();
+        for key in ["eta", "etaIdle", "editDate", "secondsSeeding"] {
+            assert_eq!(torrent[key], json[key], "{key}");
+        }
+        for key in ["addedDate", "doneDate", "secondsDownloading"] {
+            assert_eq!(None, json.get(key), "{key}");
+        }
+
+        let response: Response = serde_json::from_str(include_str!(
+            "../../tests/fixtures/transmission/4.1.0-torrent-get.json"
+        ))
+        .unwrap();
+        let json = serde_json::to_value(&response).unwrap();
+        let torrent = &json["arguments"]["torrents"][0];
+        assert_eq!(1730290012, torrent["addedDate"]);
+        assert_eq!(10500, torrent["secondsSeeding"]);
+        assert_eq!(json!([true, false]), torrent["wanted"]);
+        assert_eq!(0, torrent["error"]);
+        assert_eq!(true, torrent["peers"][0]["isUTP"]);
+        assert_eq!(
+            Some(vec![0xff; 128]),
+            serde_json::from_value::<TorrentsArguments>(torrent.clone())
+                .unwrap()
+                .pieces
+        );
+
+        for method in [
+            Method::TorrentStart(Some(TorrentId::from(7))),
+            Method::TorrentStop(None),
+            Method::TorrentGet(
+                vec![TorrentField::Id, TorrentField::PrimaryMimeType],
+                Some(vec![TorrentId::RecentlyActive]),
+                None,
+            ),
+            Method::TorrentGet(
+                vec![TorrentField::SequentialDownload],
+                Some(vec![
+                    TorrentId::from(1),
+                    TorrentId::from("0A1B".to_string()),
+                ]),
+                Some(TorrentGetFormat::Table),
+            ),
+            Method::TorrentRemove(vec![TorrentId::from(3)], true),
+            Method::TorrentSet(Box::new(TorrentSetParams {
+                ids: Some(vec![TorrentId::from(3)]),
+                sequential_download: Some(true),
+                upload_limit: Some(0),
+                ..Default::default()
+            })),
+            Method::TorrentAdd(Box::new(TorrentAddParams {
+                filename: Some("magnet:?xt=urn:btih:0A1B".to_string()),
+                paused: Some(true),
+                ..Default::default()
+            })),
+            Method::SessionStats,
+            Method::SessionGet,
+        ] {
+            let json = serde_json::to_value(&method).unwrap();
+            let round_trip: Method = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(json, serde_json::to_value(&round_trip).unwrap());
+        }
+    }
 }
@@ -0,0 +1,274 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    net::Ipv4Addr,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::transmission::api::Peer;
+
+/// Settings of the peer ban policy
+#[derive(Debug, Deserialize)]
+pub struct PeerBanSettings {
+    /// List of client names to ban. A peer is banned if its client name contains one of them (case insensitive)
+    #[serde(default)]
+    pub client_denylist: Vec<String>,
+    /// List of peer id prefixes to ban (ex: `-XL` for Xunlei)
+    #[serde(default)]
+    pub peer_id_denylist: Vec<String>,
+    /// Ban peers that download from us while reporting zero progress for an amount of minutes
+    pub zero_progress_after: Option<u64>,
+    /// Amount of hours a peer stay banned after it was last seen
+    #[serde(default = "PeerBanSettings::default_ban_duration")]
+    pub ban_duration: u64,
+    /// Lower the peer limit of torrents hit by banned peers to this value
+    pub peer_limit: Option<u64>,
+    /// Reannounce torrents hit by banned peers
+    #[serde(default)]
+    pub reannounce: bool,
+    /// Path of the P2P plaintext blocklist file written for Transmission, with the banned IPv4 addresses.
+    /// Transmission only reads its `blocklists` folder when it starts or on `blocklist-update`, so a new ban isn't enforced by Transmission before that
+    pub blocklist_path: Option<PathBuf>,
+}
+
+impl Default for PeerBanSettings {
+    fn default() -> Self {
+        PeerBanSettings {
+            client_denylist: Vec::new(),
+            peer_id_denylist: Vec::new(),
+            zero_progress_after: None,
+            ban_duration: Self::default_ban_duration(),
+            peer_limit: None,
+            reannounce: false,
+            blocklist_path: None,
+        }
+    }
+}
+
+impl PeerBanSettings {
+    fn default_ban_duration() -> u64 {
+        24
+    }
+
+    /// Method to know if the peer client is in the denylist
+    /// return `true` if it's the case
+    fn client_denied(&self, peer: &Peer) -> bool {
+        let client_name = peer.client_name.to_lowercase();
+        self.client_denylist
+            .iter()
+            .any(|client| client_name.contains(&client.to_lowercase()))
+            || self
+                .peer_id_denylist
+                .iter()
+                .any(|prefix| peer.peer_id.starts_with(prefix.as_str()))
+    }
+}
+
+/// Peer banned by the policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct BannedPeer {
+    /// Client name of the peer
+    pub client_name: String,
+    /// Last time the peer was seen and banned
+    pub last_seen: DateTime<Utc>,
+}
+
+/// State of the peer ban policy kept between fetches
+#[derive(Default, Debug)]
+pub struct PeerBan {
+    /// First time a peer was seen downloading from us with zero progress, by torrent and address
+    zero_progress: HashMap<(String, String), DateTime<Utc>>,
+    /// Peers seen with zero progress during the current cycle
+    cycle_seen: HashSet<(String, String)>,
+    /// Banned peers by address
+    banned: BTreeMap<String, BannedPeer>,
+    /// Indicate if the banned list changed since the last blocklist write
+    changed: bool,
+}
+
+impl PeerBan {
+    /// Inspect the peers of a torrent (identified by `torrent_key`)
+    /// return `true` if the torrent is hit by at least one banned peer
+    pub fn inspect(
+        &mut self,
+        settings: &PeerBanSettings,
+        torrent_key: &str,
+        peers: &[Peer],
+        now: DateTime<Utc>,
+    ) -> bool {
+        let mut hit = false;
+        for peer in peers {
+            let mut ban = settings.client_denied(peer);
+
+            if let Some(minutes) = settings.zero_progress_after
+                && peer.progress <= 0.0
+                && (peer.is_uploading_to || peer.rate_to_peer > 0)
+            {
+                let key = (torrent_key.to_string(), peer.address.clone());
+                let first_seen = *self.zero_progress.entry(key.clone()).or_insert(now);
+                self.cycle_seen.insert(key);
+                if now - first_seen >= Duration::minutes(minutes as i64) {
+                    ban = true;
+                }
+            }
+
+            if ban {
+                hit = true;
+                let banned_peer = BannedPeer {
+                    client_name: peer.client_name.clone(),
+                    last_seen: now,
+                };
+                if self
+                    .banned
+                    .insert(peer.address.clone(), banned_peer)
+                    .is_none()
+                {
+                    self.changed = true;
+                }
+            }
+        }
+
+        hit
+    }
+
+    /// Method to call at the end of a fetch cycle to forget peers that are not leeching with zero progress anymore,
+    /// and unban peers not seen for the ban duration
+    pub fn finish_cycle(&mut self, settings: &PeerBanSettings, now: DateTime<Utc>) {
+        let cycle_seen = std::mem::take(&mut self.cycle_seen);
+        self.zero_progress.retain(|key, _| cycle_seen.contains(key));
+
+        let banned_count = self.banned.len();
+        self.banned.retain(|_, banned_peer| {
+            now - banned_peer.last_seen < Duration::hours(settings.ban_duration as i64)
+        });
+        if self.banned.len() != banned_count {
+            self.changed = true;
+        }
+    }
+
+    /// Getter of the banned peers by address
+    pub fn banned(&self) -> &BTreeMap<String, BannedPeer> {
+        &self.banned
+    }
+
+    /// Render the banned peers in P2P plaintext format (`description:first_ip-last_ip`).
+    /// The format only support IPv4 addresses, IPv6 peers are left out
+    pub fn blocklist(&self) -> String {
+        self.banned
+            .iter()
+            .filter_map(|(address, banned_peer)| {
+                let address = address.parse::<Ipv4Addr>().ok()?;
+                // The client name come from the peer, so it must not break the line format
+                let description: String = banned_peer
+                    .client_name
+                    .chars()
+                    .map(|c| if c.is_control() || c == ':' { ' ' } else { c })
+                    .collect();
+                let description = match description.trim() {
+                    "" => "prosa",
+                    description => description,
+                };
+                Some(format!("{description}:{address}-{address}\n"))
+            })
+            .collect()
+    }
+
+    /// Write the blocklist file if the banned list changed
+    pub async fn write_blocklist(&mut self, settings: &PeerBanSettings) -> std::io::Result<()> {
+        if self.changed
+            && let Some(path) = &settings.blocklist_path
+        {
+            tokio::fs::write(path, self.blocklist()).await?;
+            self.changed = false;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(address: &str, client_name: &str, peer_id: &str, progress: f64) -> Peer {
+        Peer {
+            address: address.to_string(),
+            bytes_to_client: 0,
+            bytes_to_peer: 0,
+            client_name: client_name.to_string(),
+            client_is_choked: false,
+            client_is_interested: false,
+            flag_str: String::new(),
+            is_downloading_from: false,
+            is_encrypted: false,
+            is_incoming: false,
+            is_uploading_to: true,
+            is_utp: false,
+            peer_is_choked: false,
+            peer_is_interested: true,
+            peer_id: peer_id.to_string(),
+            port: 51413,
+            progress,
+            rate_to_client: 0,
+            rate_to_peer: 1024,
+        }
+    }
+
+    #[test]
+    fn peer_ban() {
+        let settings = PeerBanSettings {
+            client_denylist: vec!["xunlei".to_string()],
+            peer_id_denylist: vec!["-SD".to_string()],
+            zero_progress_after: Some(30),
+            ..Default::default()
+        };
+        let mut peer_ban = PeerBan::default();
+        let now = Utc::now();
+
+        let peers = [
+            peer("10.0.0.1", "Xunlei 0.0.1.2", "-XL0012-", 0.5),
+            peer("10.0.0.2", "Unknown", "-SD0100-", 0.5),
+            peer("10.0.0.3", "Transmission 4.0.6", "-TR4060-", 0.0),
+            peer("10.0.0.4", "qBittorrent 4.6.0", "-qB4600-", 0.2),
+        ];
+        assert!(peer_ban.inspect(&settings, "hash", &peers, now));
+        peer_ban.finish_cycle(&settings, now);
+        assert_eq!(
+            vec!["10.0.0.1", "10.0.0.2"],
+            peer_ban.banned().keys().collect::<Vec<_>>()
+        );
+
+        // The zero progress peer is banned only after the configured delay
+        assert!(peer_ban.inspect(&settings, "hash", &peers[2..], now + Duration::minutes(31)));
+        assert!(peer_ban.banned().contains_key("10.0.0.3"));
+        assert_eq!(
+            "Xunlei 0.0.1.2:10.0.0.1-10.0.0.1\nUnknown:10.0.0.2-10.0.0.2\nTransmission 4.0.6:10.0.0.3-10.0.0.3\n",
+            peer_ban.blocklist()
+        );
+        peer_ban.finish_cycle(&settings, now + Duration::minutes(31));
+
+        // Peers not seen for the ban duration are unbanned
+        let later = now + Duration::hours(24) + Duration::minutes(1);
+        assert!(peer_ban.inspect(&settings, "hash", &peers[..1], later));
+        peer_ban.finish_cycle(&settings, later);
+        assert_eq!(
+            vec!["10.0.0.1", "10.0.0.3"],
+            peer_ban.banned().keys().collect::<Vec<_>>()
+        );
+        assert!(peer_ban.changed);
+
+        // Client names can't inject lines, and IPv6 peers aren't written
+        let peers = [
+            peer("10.0.0.5", "Xunlei\n0.0.0.0-255.255.255.255", "", 0.5),
+            peer("2001:db8::1", "Xunlei", "", 0.5),
+        ];
+        assert!(peer_ban.inspect(&settings, "hash", &peers, later));
+        assert!(peer_ban.banned().contains_key("2001:db8::1"));
+        assert_eq!(
+            "Xunlei 0.0.1.2:10.0.0.1-10.0.0.1\nTransmission 4.0.6:10.0.0.3-10.0.0.3\nXunlei 0.0.0.0-255.255.255.255:10.0.0.5-10.0.0.5\n",
+            peer_ban.blocklist()
+        );
+    }
+}
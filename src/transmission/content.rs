@@ -0,0 +1,299 @@
+use std::ops::Range;
+
+use crate::transmission::api::{
+    Priority, TorrentField, TorrentId, TorrentSetParams, TorrentsArguments,
+};
+
+/// File of a torrent, joined from `files`, `fileStats`, `wanted` and `priorities`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentFile {
+    /// Index of the file in the torrent
+    pub index: usize,
+    /// Path of the file, relative to the download folder
+    pub path: String,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// Completed bytes of the file
+    pub bytes_completed: u64,
+    /// Indicate if the file is wanted
+    pub wanted: bool,
+    /// Download priority of the file
+    pub priority: Priority,
+    /// Pieces containing the file `[begin_piece, end_piece)`
+    pub pieces: Range<u32>,
+}
+
+impl ContentFile {
+    /// Getter of the completion of the file in bytes. Range is [0..1]
+    pub fn percent_done(&self) -> f64 {
+        if self.size > 0 {
+            self.bytes_completed as f64 / self.size as f64
+        } else {
+            1.0
+        }
+    }
+
+    /// Indicate if the file is completely downloaded
+    pub fn is_complete(&self) -> bool {
+        self.bytes_completed >= self.size
+    }
+}
+
+/// Content view of a torrent, with per-file records and piece completion
+#[derive(Debug, Clone, Default)]
+pub struct TorrentContent {
+    /// Files of the torrent, in the torrent order
+    pub files: Vec<ContentFile>,
+    /// Number of pieces of the torrent
+    pub piece_count: u32,
+    /// Size of a piece in bytes
+    pub piece_size: u64,
+    /// Bitfield of the pieces we have (first piece is the most significant bit of the first byte)
+    pieces: Vec<u8>,
+    /// Number of connected peers that have each piece, or -1 if we already have the piece
+    availability: Vec<i64>,
+}
+
+impl TorrentContent {
+    /// Fields to request on `torrent-get` to build a complete content view
+    pub const FIELDS: [TorrentField; 7] = [
+        TorrentField::Files,
+        TorrentField::FileStats,
+        TorrentField::Pieces,
+        TorrentField::PieceCount,
+        TorrentField::PieceSize,
+        TorrentField::Availability,
+        TorrentField::SequentialDownload,
+    ];
+
+    /// Build the content view of a torrent.
+    /// Need at least the `files` field, other fields (`fileStats`, `wanted`, `priorities`, `pieces`, `pieceCount`, `pieceSize`, `availability`) complete the view.
+    /// Without `begin_piece`/`end_piece` (Transmission 3), file pieces are computed from the file sizes and `pieceSize`
+    pub fn from_torrent(torrent: &TorrentsArguments) -> Option<TorrentContent> {
+        let files = torrent.files.as_ref()?;
+        let piece_size = torrent.piece_size.unwrap_or_default();
+        let mut file_start = 0u64;
+        Some(TorrentContent {
+            files: files
+                .iter()
+                .enumerate()
+                .map(|(index, file)| {
+                    let pieces = match (file.begin_piece, file.end_piece) {
+                        (Some(begin), Some(end)) => begin..end,
+                        _ if piece_size > 0 => {
+                            let begin = file_start / piece_size;
+                            let end = (file_start + file.length).div_ceil(piece_size);
+                            begin as u32..end.max(begin) as u32
+                        }
+                        _ => 0..0,
+                    };
+                    file_start += file.length;
+                    let file_stat = torrent.file_stats.as_ref().and_then(|s| s.get(index));
+                    ContentFile {
+                        index,
+                        path: file.name.clone(),
+                        size: file.length,
+                        bytes_completed: file_stat
+                            .map(|s| s.bytes_completed)
+                            .unwrap_or(file.bytes_completed),
+                        wanted: file_stat.map(|s| s.wanted).unwrap_or_else(|| {
+                            torrent
+                                .wanted
+                                .as_ref()
+                                .and_then(|w| w.get(index).copied())
+                                .unwrap_or(true)
+                        }),
+                        priority: file_stat.map(|s| s.priority).unwrap_or_else(|| {
+                            torrent
+                                .priorities
+                                .as_ref()
+                                .and_then(|p| p.get(index).copied())
+                                .unwrap_or(Priority::Normal)
+                        }),
+                        pieces,
+                    }
+                })
+                .collect(),
+            piece_count: torrent.piece_count.unwrap_or_default(),
+            piece_size,
+            pieces: torrent.pieces.clone().unwrap_or_default(),
+            availability: torrent.availability.clone().unwrap_or_default(),
+        })
+    }
+
+    /// Getter of a file by its index
+    pub fn file(&self, index: usize) -> Option<&ContentFile> {
+        self.files.get(index)
+    }
+
+    /// Method to know if we have a piece
+    pub fn has_piece(&self, piece: u32) -> bool {
+        self.pieces
+            .get(piece as usize / 8)
+            .is_some_and(|byte| byte & (0x80 >> (piece % 8)) != 0)
+    }
+
+    /// Getter of the number of connected peers that have a piece, or `None` if we already have it
+    pub fn piece_availability(&self, piece: u32) -> Option<u32> {
+        match self.availability.get(piece as usize) {
+            Some(count) if *count >= 0 => Some(*count as u32),
+            Some(_) => None,
+            None if self.has_piece(piece) => None,
+            None => Some(0),
+        }
+    }
+
+    /// Getter of the piece completion of a file. Range is [0..1]
+    pub fn file_piece_completion(&self, index: usize) -> Option<f64> {
+        let file = self.files.get(index)?;
+        if file.pieces.is_empty() {
+            return Some(1.0);
+        }
+
+        let have = file.pieces.clone().filter(|p| self.has_piece(*p)).count();
+        Some(have as f64 / file.pieces.len() as f64)
+    }
+
+    /// Getter of the availability of a file: part of its pieces that we have or that at least one connected peer have. Range is [0..1]
+    pub fn file_availability(&self, index: usize) -> Option<f64> {
+        let file = self.files.get(index)?;
+        if file.pieces.is_empty() {
+            return Some(1.0);
+        }
+
+        let available = file
+            .pieces
+            .clone()
+            .filter(|p| self.piece_availability(*p).is_none_or(|count| count > 0))
+            .count();
+        Some(available as f64 / file.pieces.len() as f64)
+    }
+
+    /// Getter of the piece that contain a byte offset of a file
+    pub fn piece_at(&self, index: usize, offset: u64) -> Option<u32> {
+        let file = self.files.get(index)?;
+        if self.piece_size == 0 || offset >= file.size.max(1) {
+            return None;
+        }
+
+        // Files are laid out contiguously, so the file start in its first piece is given by the previous files
+        let file_start: u64 = self.files[..index].iter().map(|f| f.size).sum();
+        let piece_start = file_start.saturating_sub(file.pieces.start as u64 * self.piece_size);
+        let piece = file.pieces.start as u64 + (piece_start + offset) / self.piece_size;
+        Some((piece as u32).min(file.pieces.end.saturating_sub(1).max(file.pieces.start)))
+    }
+
+    /// Build the torrent mutation to stream a file from a byte offset:
+    /// enable sequential download from the piece containing the offset, and raise the file priority.
+    /// return `None` if the file or the offset doesn't exist
+    pub fn stream_from(
+        &self,
+        id: TorrentId,
+        index: usize,
+        offset: u64,
+    ) -> Option<TorrentSetParams> {
+        let piece = self.piece_at(index, offset)?;
+        Some(TorrentSetParams {
+            ids: Some(vec![id]),
+            files_wanted: Some(vec![index]),
+            priority_high: Some(vec![index as u32]),
+            sequential_download: Some(true),
+            sequential_download_from_piece: Some(piece),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn torrent_content() {
+        let torrent: TorrentsArguments = serde_json::from_str(
+            r#"{
+                "files": [
+                    { "bytesCompleted": 32768, "length": 40000, "name": "Show/Show.S01E01.mkv", "begin_piece": 0, "end_piece": 3 },
+                    { "bytesCompleted": 0, "length": 1000, "name": "Show/Show.nfo", "begin_piece": 2, "end_piece": 3 },
+                    { "bytesCompleted": 0, "length": 20000, "name": "Show/Sample/sample.mkv", "begin_piece": 3, "end_piece": 5 }
+                ],
+                "fileStats": [
+                    { "bytesCompleted": 32768, "wanted": true, "priority": 1 },
+                    { "bytesCompleted": 0, "wanted": false, "priority": 0 },
+                    { "bytesCompleted": 0, "wanted": true, "priority": -1 }
+                ],
+                "pieces": "wA==",
+                "pieceCount": 5,
+                "pieceSize": 16384,
+                "availability": [-1, -1, 3, 0, 2]
+            }"#,
+        )
+        .unwrap();
+
+        let content = TorrentContent::from_torrent(&torrent).unwrap();
+        assert_eq!(3, content.files.len());
+        let video = content.file(0).unwrap();
+        assert_eq!("Show/Show.S01E01.mkv", video.path);
+        assert_eq!(Priority::High, video.priority);
+        assert_eq!(0..3, video.pieces);
+        assert!(!video.is_complete());
+        assert!(!content.file(1).unwrap().wanted);
+        assert_eq!(Priority::Low, content.file(2).unwrap().priority);
+
+        assert!(content.has_piece(0));
+        assert!(content.has_piece(1));
+        assert!(!content.has_piece(2));
+        assert_eq!(None, content.piece_availability(1));
+        assert_eq!(Some(3), content.piece_availability(2));
+
+        assert_eq!(Some(2.0 / 3.0), content.file_piece_completion(0));
+        assert_eq!(Some(0.0), content.file_piece_completion(1));
+        assert_eq!(Some(1.0), content.file_availability(0));
+        assert_eq!(Some(0.5), content.file_availability(2));
+        assert_eq!(None, content.file_availability(3));
+
+        // Transmission 3 doesn't give the file pieces, they are computed from the file sizes: the sample start at byte 41000
+        let mut torrent = torrent;
+        for file in torrent.files.iter_mut().flatten() {
+            file.begin_piece = None;
+            file.end_piece = None;
+        }
+        let content = TorrentContent::from_torrent(&torrent).unwrap();
+        assert_eq!(0..3, content.file(0).unwrap().pieces);
+        assert_eq!(2..3, content.file(1).unwrap().pieces);
+        assert_eq!(2..4, content.file(2).unwrap().pieces);
+        assert_eq!(Some(2.0 / 3.0), content.file_piece_completion(0));
+        assert_eq!(Some(0.5), content.file_availability(2));
+        assert_eq!(Some(2), content.piece_at(0, 32768));
+    }
+
+    #[test]
+    fn stream_from() {
+        let torrent: TorrentsArguments = serde_json::from_str(
+            r#"{
+                "files": [
+                    { "bytesCompleted": 0, "length": 20000, "name": "Movie/Movie.nfo", "begin_piece": 0, "end_piece": 2 },
+                    { "bytesCompleted": 0, "length": 100000, "name": "Movie/Movie.mkv", "begin_piece": 1, "end_piece": 8 }
+                ],
+                "pieceCount": 8,
+                "pieceSize": 16384
+            }"#,
+        )
+        .unwrap();
+        let content = TorrentContent::from_torrent(&torrent).unwrap();
+
+        // The video start in the second piece, at 20000 - 16384 = 3616 bytes
+        assert_eq!(Some(1), content.piece_at(1, 0));
+        assert_eq!(Some(1), content.piece_at(1, 12767));
+        assert_eq!(Some(2), content.piece_at(1, 12768));
+        assert_eq!(Some(7), content.piece_at(1, 99999));
+        assert_eq!(None, content.piece_at(1, 100000));
+        assert_eq!(None, content.piece_at(2, 0));
+
+        let params = content.stream_from(7.into(), 1, 50000).unwrap();
+        assert_eq!(Some(vec![TorrentId::from(7)]), params.ids);
+        assert_eq!(Some(vec![1]), params.priority_high);
+        assert_eq!(Some(true), params.sequential_download);
+        assert_eq!(Some(4), params.sequential_download_from_piece);
+    }
+}
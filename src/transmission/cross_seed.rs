@@ -0,0 +1,154 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::transmission::api::{Id, TorrentField, TorrentsArguments};
+
+/// Settings of the cross-seeding detection
+#[derive(Default, Debug, Deserialize)]
+pub struct CrossSeedSettings {
+    /// Match torrents with their file list instead of their name and total size.
+    /// Files of all torrents are requested on each fetch
+    #[serde(default)]
+    pub match_files: bool,
+}
+
+impl CrossSeedSettings {
+    /// Getter of the fields to request on `torrent-get` to detect cross-seeded torrents
+    pub fn fields(&self) -> Vec<TorrentField> {
+        let mut fields = vec![
+            TorrentField::Name,
+            TorrentField::TotalSize,
+            TorrentField::DownloadDir,
+        ];
+        if self.match_files {
+            fields.push(TorrentField::Files);
+        }
+        fields
+    }
+}
+
+/// Signature of the content of a torrent, shared by torrents cross-seeded on several trackers
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContentSignature {
+    /// Download folder with the sorted list of file paths and sizes
+    Files(Option<String>, Vec<(String, u64)>),
+    /// Download folder with the name and total size of the torrent
+    NameSize(Option<String>, String, u64),
+}
+
+impl ContentSignature {
+    /// Build the content signature of a torrent, from its files if they are known, or from its name and total size
+    pub fn from_torrent(torrent: &TorrentsArguments) -> Option<ContentSignature> {
+        if let Some(files) = torrent.files.as_ref().filter(|f| !f.is_empty()) {
+            let mut files: Vec<(String, u64)> =
+                files.iter().map(|f| (f.name.clone(), f.length)).collect();
+            files.sort();
+            Some(ContentSignature::Files(torrent.download_dir.clone(), files))
+        } else {
+            Some(ContentSignature::NameSize(
+                torrent.download_dir.clone(),
+                torrent.name.clone()?,
+                torrent.total_size?,
+            ))
+        }
+    }
+}
+
+/// Index of torrents grouped by content signature
+#[derive(Default, Debug)]
+pub struct CrossSeedIndex {
+    signatures: HashMap<Id, ContentSignature>,
+    groups: HashMap<ContentSignature, HashSet<Id>>,
+}
+
+impl CrossSeedIndex {
+    /// Build the index from all the torrents
+    pub fn new<'a>(torrents: impl Iterator<Item = (&'a Id, &'a TorrentsArguments)>) -> Self {
+        let mut index = CrossSeedIndex::default();
+        for (id, torrent) in torrents {
+            if let Some(signature) = ContentSignature::from_torrent(torrent) {
+                index
+                    .groups
+                    .entry(signature.clone())
+                    .or_default()
+                    .insert(id.clone());
+                index.signatures.insert(id.clone(), signature);
+            }
+        }
+
+        index
+    }
+
+    /// Getter of the other torrents that share the content of the torrent
+    pub fn cross_seeds(&self, id: &Id) -> Vec<&Id> {
+        self.signatures
+            .get(id)
+            .and_then(|signature| self.groups.get(signature))
+            .map(|group| group.iter().filter(|i| *i != id).collect())
+            .unwrap_or_default()
+    }
+
+    /// Split the torrents to remove between the ones whose local data can be deleted,
+    /// and the ones whose content is still seeded by a torrent that is not removed.
+    /// Torrents with an unknown content are kept with their local data
+    pub fn split_removal(&self, removed: Vec<Id>) -> (Vec<Id>, Vec<Id>) {
+        let removed_set: HashSet<&Id> = removed.iter().collect();
+        removed.iter().cloned().partition(|id| {
+            self.signatures
+                .get(id)
+                .and_then(|signature| self.groups.get(signature))
+                .is_some_and(|group| group.iter().all(|i| removed_set.contains(i)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transmission::api::TorrentId;
+
+    use super::*;
+
+    #[test]
+    fn cross_seed() {
+        let torrents: HashMap<Id, TorrentsArguments> = serde_json::from_str::<
+            Vec<TorrentsArguments>,
+        >(
+            r#"[
+                { "id": 1, "name": "Movie", "totalSize": 1000, "downloadDir": "/data" },
+                { "id": 2, "name": "Movie", "totalSize": 1000, "downloadDir": "/data" },
+                { "id": 3, "name": "Movie", "totalSize": 1000, "downloadDir": "/other" },
+                { "id": 4, "name": "Show", "totalSize": 10, "downloadDir": "/data", "files": [
+                    { "bytesCompleted": 10, "length": 4, "name": "Show/a.mkv", "begin_piece": 0, "end_piece": 1 },
+                    { "bytesCompleted": 10, "length": 6, "name": "Show/b.mkv", "begin_piece": 1, "end_piece": 2 }
+                ] },
+                { "id": 5, "name": "Show renamed", "totalSize": 10, "downloadDir": "/data", "files": [
+                    { "bytesCompleted": 10, "length": 6, "name": "Show/b.mkv", "begin_piece": 1, "end_piece": 2 },
+                    { "bytesCompleted": 10, "length": 4, "name": "Show/a.mkv", "begin_piece": 0, "end_piece": 1 }
+                ] }
+            ]"#,
+        )
+        .unwrap()
+        .into_iter()
+        .filter_map(|t| match &t.id {
+            Some(TorrentId::Id(id)) => Some((id.clone(), t)),
+            _ => None,
+        })
+        .collect();
+
+        let index = CrossSeedIndex::new(torrents.iter());
+        assert_eq!(vec![&Id::Id(2)], index.cross_seeds(&Id::Id(1)));
+        assert!(index.cross_seeds(&Id::Id(3)).is_empty());
+        assert_eq!(vec![&Id::Id(5)], index.cross_seeds(&Id::Id(4)));
+
+        // The content of torrent 1 is still seeded by torrent 2
+        let (delete, keep) = index.split_removal(vec![Id::Id(1), Id::Id(3), Id::Id(6)]);
+        assert_eq!(vec![Id::Id(3)], delete);
+        assert_eq!(vec![Id::Id(1), Id::Id(6)], keep);
+
+        // Last torrents that reference the content
+        let (delete, keep) = index.split_removal(vec![Id::Id(4), Id::Id(5)]);
+        assert_eq!(vec![Id::Id(4), Id::Id(5)], delete);
+        assert!(keep.is_empty());
+    }
+}
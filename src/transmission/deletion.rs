@@ -0,0 +1,125 @@
+use std::path::{Component, Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::transmission::api::{TorrentField, TorrentsArguments};
+
+/// Settings of the local data deletion of removed torrents
+#[derive(Default, Debug, Deserialize)]
+pub struct DeletionSettings {
+    /// Delete local data of torrents from these trackers (part of the announce URL).
+    /// If no tracker and no label are set, the policy apply to all torrents
+    #[serde(default)]
+    pub trackers: Vec<String>,
+    /// Delete local data of torrents with one of these labels
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Local data are never deleted outside these download folders
+    #[serde(default)]
+    pub download_roots: Vec<PathBuf>,
+    /// Local data of torrents with one of these labels are never deleted
+    #[serde(default)]
+    pub protected_labels: Vec<String>,
+}
+
+impl DeletionSettings {
+    /// Fields to request on `torrent-get` to apply the deletion policy
+    pub const FIELDS: [TorrentField; 4] = [
+        TorrentField::DownloadDir,
+        TorrentField::Labels,
+        TorrentField::SizeWhenDone,
+        TorrentField::TrackerList,
+    ];
+
+    /// Method to know if the policy select the torrent from its trackers and labels
+    fn selected(&self, torrent: &TorrentsArguments) -> bool {
+        if self.trackers.is_empty() && self.labels.is_empty() {
+            return true;
+        }
+
+        torrent.tracker_list.as_ref().is_some_and(|tracker_list| {
+            self.trackers
+                .iter()
+                .any(|tracker| tracker_list.contains(tracker.as_str()))
+        }) || torrent
+            .labels
+            .as_ref()
+            .is_some_and(|labels| labels.iter().any(|label| self.labels.contains(label)))
+    }
+
+    /// Method to know if the torrent have a protected label
+    /// return `true` if it's the case
+    fn protected(&self, torrent: &TorrentsArguments) -> bool {
+        torrent.labels.as_ref().is_some_and(|labels| {
+            labels
+                .iter()
+                .any(|label| self.protected_labels.contains(label))
+        })
+    }
+
+    /// Method to know if the download folder is inside one of the configured roots.
+    /// Relative folders, or with parent components are never accepted
+    fn in_download_roots(&self, download_dir: &str) -> bool {
+        let download_dir = Path::new(download_dir);
+        download_dir.is_absolute()
+            && !download_dir
+                .components()
+                .any(|c| matches!(c, Component::ParentDir | Component::CurDir))
+            && self
+                .download_roots
+                .iter()
+                .any(|root| download_dir.starts_with(root))
+    }
+
+    /// Method to know if local data of the torrent can be deleted when it's removed
+    pub fn allow(&self, torrent: &TorrentsArguments) -> bool {
+        self.selected(torrent)
+            && !self.protected(torrent)
+            && torrent
+                .download_dir
+                .as_deref()
+                .is_some_and(|download_dir| self.in_download_roots(download_dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(json: &str) -> TorrentsArguments {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn deletion_policy() {
+        let settings = DeletionSettings {
+            trackers: vec!["public.example".to_string()],
+            labels: vec!["iso".to_string()],
+            download_roots: vec![PathBuf::from("/data/torrents")],
+            protected_labels: vec!["keep".to_string()],
+        };
+
+        assert!(settings.allow(&torrent(
+            r#"{ "downloadDir": "/data/torrents/movies", "trackerList": "https://public.example/announce" }"#
+        )));
+        assert!(settings.allow(&torrent(
+            r#"{ "downloadDir": "/data/torrents", "labels": ["iso"] }"#
+        )));
+        // Not selected by trackers or labels
+        assert!(!settings.allow(&torrent(
+            r#"{ "downloadDir": "/data/torrents", "trackerList": "https://private.example/announce" }"#
+        )));
+        // Protected label
+        assert!(!settings.allow(&torrent(
+            r#"{ "downloadDir": "/data/torrents", "labels": ["iso", "keep"] }"#
+        )));
+        // Outside of the download roots
+        assert!(!settings.allow(&torrent(
+            r#"{ "downloadDir": "/data/torrents-old", "labels": ["iso"] }"#
+        )));
+        assert!(!settings.allow(&torrent(
+            r#"{ "downloadDir": "/data/torrents/../home", "labels": ["iso"] }"#
+        )));
+        assert!(!settings.allow(&torrent(r#"{ "labels": ["iso"] }"#)));
+    }
+}
@@ -0,0 +1,313 @@
+use std::{collections::HashMap, fmt};
+
+use chrono::{DateTime, Utc};
+use prosa::core::msg::Tvf;
+use serde::{Deserialize, Serialize};
+
+use crate::transmission::api::{StatErrType, Status, TorrentField, TorrentsArguments};
+
+/// TVF field id of the event kind
+pub const EVENT_KIND: usize = 1;
+/// TVF field id of the torrent hash
+pub const EVENT_HASH: usize = 2;
+/// TVF field id of the torrent name
+pub const EVENT_NAME: usize = 3;
+/// TVF field id of the event detail (error message, new location, labels)
+pub const EVENT_DETAIL: usize = 4;
+/// TVF field id of the event date
+pub const EVENT_DATE: usize = 5;
+
+/// Kind of torrent lifecycle event
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TorrentEventKind {
+    /// Torrent added to Transmission
+    Added,
+    /// Metadata of the torrent (magnet link) are now complete
+    MetadataComplete,
+    /// Torrent start to download
+    DownloadStarted,
+    /// Wanted files of the torrent are completely downloaded
+    Completed,
+    /// Torrent start to seed
+    SeedingStarted,
+    /// Torrent get an error
+    Errored,
+    /// Torrent removed from Transmission
+    Removed,
+    /// Torrent data moved to an other location
+    Moved,
+    /// Labels of the torrent changed
+    LabelChanged,
+}
+
+/// Named as serialized, like in the settings of the notifiers
+impl fmt::Display for TorrentEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => f.write_str(&name),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+/// Torrent lifecycle event
+#[derive(Debug, Clone, PartialEq)]
+pub struct TorrentEvent {
+    /// Kind of the event
+    pub kind: TorrentEventKind,
+    /// Hash of the torrent
+    pub hash: String,
+    /// Name of the torrent
+    pub name: Option<String>,
+    /// Detail of the event (error message, new location, labels)
+    pub detail: Option<String>,
+    /// Date of the event
+    pub date: DateTime<Utc>,
+}
+
+impl TorrentEvent {
+    /// Encode the event into a TVF message
+    pub fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        tvf.put_string(EVENT_KIND, self.kind.to_string());
+        tvf.put_string(EVENT_HASH, self.hash.clone());
+        if let Some(name) = &self.name {
+            tvf.put_string(EVENT_NAME, name.clone());
+        }
+        if let Some(detail) = &self.detail {
+            tvf.put_string(EVENT_DETAIL, detail.clone());
+        }
+        tvf.put_datetime(EVENT_DATE, self.date.naive_utc());
+        tvf
+    }
+
+    /// Decode an event from a TVF message
+    pub fn from_tvf<M>(tvf: &M) -> Option<TorrentEvent>
+    where
+        M: Tvf,
+    {
+        Some(TorrentEvent {
+            kind: serde_json::from_value(serde_json::Value::String(
+                tvf.get_string(EVENT_KIND).ok()?.into_owned(),
+            ))
+            .ok()?,
+            hash: tvf.get_string(EVENT_HASH).ok()?.into_owned(),
+            name: tvf.get_string(EVENT_NAME).ok().map(|n| n.into_owned()),
+            detail: tvf.get_string(EVENT_DETAIL).ok().map(|d| d.into_owned()),
+            date: tvf.get_datetime(EVENT_DATE).ok()?.and_utc(),
+        })
+    }
+}
+
+/// State of a torrent kept to detect changes between two fetches
+#[derive(Debug, Clone, PartialEq)]
+struct TorrentSnapshot {
+    name: Option<String>,
+    status: Option<Status>,
+    percent_done: f64,
+    metadata_percent_complete: f64,
+    errored: bool,
+    download_dir: Option<String>,
+    labels: Vec<String>,
+}
+
+impl From<&TorrentsArguments> for TorrentSnapshot {
+    fn from(torrent: &TorrentsArguments) -> Self {
+        TorrentSnapshot {
+            name: torrent.name.clone(),
+            status: torrent.status,
+            percent_done: torrent.percent_done.unwrap_or_default(),
+            metadata_percent_complete: torrent.metadata_percent_complete.unwrap_or(1.0),
+            errored: torrent
+                .error
+                .is_some_and(|e| e != StatErrType::Ok && e != StatErrType::TrackerWarning),
+            download_dir: torrent.download_dir.clone(),
+            labels: torrent.labels.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Detect torrent lifecycle events by diffing successive `torrent-get` snapshots by hash
+#[derive(Debug, Default)]
+pub struct TorrentEventDetector {
+    snapshots: Option<HashMap<String, TorrentSnapshot>>,
+}
+
+impl TorrentEventDetector {
+    /// Fields to request on `torrent-get` to detect events
+    pub const FIELDS: [TorrentField; 9] = [
+        TorrentField::HashString,
+        TorrentField::Name,
+        TorrentField::Status,
+        TorrentField::PercentDone,
+        TorrentField::MetadataPercentComplete,
+        TorrentField::Error,
+        TorrentField::ErrorString,
+        TorrentField::DownloadDir,
+        TorrentField::Labels,
+    ];
+
+    /// Update the snapshot with all the current torrents, and return events since the last update.
+    /// The first update only record the snapshot without any event
+    pub fn update<'a>(
+        &mut self,
+        torrents: impl Iterator<Item = &'a TorrentsArguments>,
+    ) -> Vec<TorrentEvent> {
+        let date = Utc::now();
+        let mut events = Vec::new();
+        let mut snapshots = HashMap::new();
+        let mut previous_snapshots = self.snapshots.take();
+        for torrent in torrents {
+            let Some(hash) = &torrent.hash_string else {
+                continue;
+            };
+
+            let snapshot = TorrentSnapshot::from(torrent);
+            if let Some(previous_snapshots) = previous_snapshots.as_mut() {
+                let mut push_event = |kind, detail| {
+                    events.push(TorrentEvent {
+                        kind,
+                        hash: hash.clone(),
+                        name: snapshot.name.clone(),
+                        detail,
+                        date,
+                    })
+                };
+
+                match previous_snapshots.remove(hash) {
+                    None => push_event(TorrentEventKind::Added, None),
+                    Some(previous) => {
+                        if previous.metadata_percent_complete < 1.0
+                            && snapshot.metadata_percent_complete >= 1.0
+                        {
+                            push_event(TorrentEventKind::MetadataComplete, None);
+                        }
+                        if previous.status != Some(Status::Download)
+                            && snapshot.status == Some(Status::Download)
+                        {
+                            push_event(TorrentEventKind::DownloadStarted, None);
+                        }
+                        if previous.percent_done < 1.0 && snapshot.percent_done >= 1.0 {
+                            push_event(TorrentEventKind::Completed, None);
+                        }
+                        if previous.status != Some(Status::Seed)
+                            && snapshot.status == Some(Status::Seed)
+                        {
+                            push_event(TorrentEventKind::SeedingStarted, None);
+                        }
+                        if !previous.errored && snapshot.errored {
+                            push_event(TorrentEventKind::Errored, torrent.error_string.clone());
+                        }
+                        if previous.download_dir.is_some()
+                            && previous.download_dir != snapshot.download_dir
+                        {
+                            push_event(TorrentEventKind::Moved, snapshot.download_dir.clone());
+                        }
+                        if previous.labels != snapshot.labels {
+                            push_event(
+                                TorrentEventKind::LabelChanged,
+                                Some(snapshot.labels.join(",")),
+                            );
+                        }
+                    }
+                }
+            }
+
+            snapshots.insert(hash.clone(), snapshot);
+        }
+
+        // Torrents that are not here anymore have been removed
+        if let Some(previous_snapshots) = previous_snapshots {
+            for (hash, previous) in previous_snapshots {
+                events.push(TorrentEvent {
+                    kind: TorrentEventKind::Removed,
+                    hash,
+                    name: previous.name,
+                    detail: None,
+                    date,
+                });
+            }
+        }
+
+        self.snapshots = Some(snapshots);
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prosa_utils::msg::simple_string_tvf::SimpleStringTvf;
+
+    use super::*;
+
+    fn torrents(json: &str) -> Vec<TorrentsArguments> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn torrent_events() {
+        let mut detector = TorrentEventDetector::default();
+        assert!(
+            detector
+                .update(
+                    torrents(
+                        r#"[
+                            { "hashString": "a", "name": "A", "status": 4, "percentDone": 0.5, "labels": [] },
+                            { "hashString": "b", "name": "B", "status": 6, "percentDone": 1.0, "labels": [] }
+                        ]"#
+                    )
+                    .iter()
+                )
+                .is_empty()
+        );
+
+        let events = detector.update(
+            torrents(
+                r#"[
+                    { "hashString": "a", "name": "A", "status": 6, "percentDone": 1.0, "labels": ["tv"] },
+                    { "hashString": "c", "name": "C", "status": 0, "percentDone": 0.0, "metadataPercentComplete": 0.0 }
+                ]"#,
+            )
+            .iter(),
+        );
+        let kinds: Vec<(&str, TorrentEventKind)> =
+            events.iter().map(|e| (e.hash.as_str(), e.kind)).collect();
+        assert_eq!(
+            vec![
+                ("a", TorrentEventKind::Completed),
+                ("a", TorrentEventKind::SeedingStarted),
+                ("a", TorrentEventKind::LabelChanged),
+                ("c", TorrentEventKind::Added),
+                ("b", TorrentEventKind::Removed),
+            ],
+            kinds
+        );
+
+        let events = detector.update(
+            torrents(
+                r#"[
+                    { "hashString": "a", "name": "A", "status": 6, "percentDone": 1.0, "labels": ["tv"], "error": 3, "errorString": "No space left on device" },
+                    { "hashString": "c", "name": "C", "status": 4, "percentDone": 0.0, "metadataPercentComplete": 1.0 }
+                ]"#,
+            )
+            .iter(),
+        );
+        assert_eq!(3, events.len());
+        assert_eq!(TorrentEventKind::Errored, events[0].kind);
+        assert_eq!(Some("No space left on device"), events[0].detail.as_deref());
+        assert_eq!(TorrentEventKind::MetadataComplete, events[1].kind);
+        assert_eq!(TorrentEventKind::DownloadStarted, events[2].kind);
+
+        let tvf: SimpleStringTvf = events[0].to_tvf();
+        let event = TorrentEvent::from_tvf(&tvf).unwrap();
+        assert_eq!(events[0].kind, event.kind);
+        assert_eq!("errored", tvf.get_string(EVENT_KIND).unwrap().as_str());
+        assert_eq!(events[0].hash, event.hash);
+        assert_eq!(events[0].detail, event.detail);
+        assert_eq!(events[0].date.timestamp(), event.date.timestamp());
+    }
+}
@@ -0,0 +1,528 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use http::{Request, StatusCode};
+use http_body_util::{BodyExt as _, Full};
+use hyper::client::conn::http1;
+use hyper_util::rt::TokioIo;
+use prosa::io::stream::TargetSetting;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::transmission::api::{Method, Response, TorrentAddParams};
+
+/// Maximum number of item GUIDs remembered by feed
+const MAX_SEEN_ITEMS: usize = 1024;
+
+/// Timeout to retrieve a feed
+const FEED_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Season and episode of a title (`Show.S01E02` or `Show 1x02`)
+static EPISODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(.+?)[\s._\-\[(]+(?:s(\d{1,3})[\s._]?e(\d{1,4})|(\d{1,2})x(\d{2,3}))\b")
+        .unwrap()
+});
+
+/// Settings of an RSS or Atom feed
+#[derive(Debug, Deserialize)]
+pub struct FeedSettings {
+    /// Name of the feed, to remember its seen items
+    pub name: String,
+    /// Target of the feed URL, with its SSL and proxy settings if needed
+    pub target: TargetSetting,
+    /// Poll interval of the feed in minutes
+    #[serde(default = "FeedSettings::default_interval")]
+    pub interval: u64,
+    /// Regex filters on item titles. An item is added if it matches one of them, or if there is no filter
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Regex of item titles to exclude
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Add each episode of a show only once, from the season and episode of the item title
+    #[serde(default)]
+    pub episode_dedup: bool,
+    /// Download directory of the torrents added from this feed
+    pub download_dir: Option<String>,
+    /// Labels set on the torrents added from this feed
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Add the torrents without starting them
+    #[serde(default)]
+    pub paused: bool,
+}
+
+impl FeedSettings {
+    fn default_interval() -> u64 {
+        15
+    }
+}
+
+/// Item of a feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedItem {
+    /// Unique id of the item (`guid` of RSS, `id` of Atom), or its URL
+    pub guid: String,
+    pub title: String,
+    /// URL of the .torrent file, or magnet link
+    pub url: String,
+}
+
+/// Getter of the child elements of a node with a given name
+fn children<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    node.children()
+        .filter(move |c| c.is_element() && c.tag_name().name() == name)
+}
+
+/// Parse the items of an RSS or Atom feed.
+/// The torrent URL is taken from the enclosure, or from the link of the item
+pub fn parse_feed(xml: &str) -> Result<Vec<FeedItem>, roxmltree::Error> {
+    let document = roxmltree::Document::parse(xml)?;
+
+    Ok(document
+        .descendants()
+        .filter(|n| n.is_element() && matches!(n.tag_name().name(), "item" | "entry"))
+        .filter_map(|item| {
+            let text = |name| {
+                children(item, name)
+                    .find_map(|c| c.text())
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+            };
+            let url = children(item, "enclosure")
+                .find_map(|c| c.attribute("url"))
+                .or_else(|| {
+                    children(item, "link")
+                        .find(|c| c.attribute("rel") == Some("enclosure"))
+                        .and_then(|c| c.attribute("href"))
+                })
+                .or_else(|| text("link"))
+                .or_else(|| children(item, "link").find_map(|c| c.attribute("href")))?
+                .to_string();
+            Some(FeedItem {
+                guid: text("guid")
+                    .or_else(|| text("id"))
+                    .map(String::from)
+                    .unwrap_or_else(|| url.clone()),
+                title: text("title").unwrap_or_default().to_string(),
+                url,
+            })
+        })
+        .collect())
+}
+
+/// Getter of the episode of a title, as a `show s01e02` key
+pub fn episode_key(title: &str) -> Option<String> {
+    let captures = EPISODE.captures(title)?;
+    let show = captures[1]
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let (season, episode) = match (captures.get(2), captures.get(3)) {
+        (Some(season), Some(episode)) => (season, episode),
+        _ => (captures.get(4)?, captures.get(5)?),
+    };
+    Some(format!(
+        "{show} s{:02}e{:02}",
+        season.as_str().parse::<u32>().ok()?,
+        episode.as_str().parse::<u32>().ok()?
+    ))
+}
+
+/// Retrieve the items of a feed
+async fn fetch_feed(target: &TargetSetting) -> Result<Vec<FeedItem>, String> {
+    let stream = target
+        .connect()
+        .await
+        .map_err(|e| format!("Can't connect to {target}: {e}"))?;
+    let (mut sender, connection) = http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(|e| format!("HTTP handshake error: {e}"))?;
+    let connection = tokio::spawn(connection);
+
+    let mut path = target.url.path().to_string();
+    if let Some(query) = target.url.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+    let mut request_builder = Request::get(path).header(
+        hyper::header::ACCEPT,
+        "application/rss+xml, application/atom+xml, application/xml, text/xml",
+    );
+    if let Some(host) = target.url.host_str() {
+        request_builder = request_builder.header(
+            hyper::header::HOST,
+            match target.url.port() {
+                Some(port) => format!("{host}:{port}"),
+                None => host.to_string(),
+            },
+        );
+    }
+    if let Some(authorization) = target.get_authentication() {
+        request_builder = request_builder.header(hyper::header::AUTHORIZATION, authorization);
+    }
+    let request = request_builder
+        .body(Full::<Bytes>::default())
+        .map_err(|e| format!("can't build HTTP request: {e}"))?;
+
+    let response = sender
+        .send_request(request)
+        .await
+        .map_err(|e| format!("HTTP error: {e}"));
+    let body = match response {
+        Ok(response) if response.status() == StatusCode::OK => response
+            .collect()
+            .await
+            .map(|b| b.to_bytes())
+            .map_err(|e| format!("HTTP body error: {e}")),
+        Ok(response) => Err(format!(
+            "Receive error from HTTP remote: {}",
+            response.status()
+        )),
+        Err(e) => Err(e),
+    };
+    connection.abort();
+
+    parse_feed(&String::from_utf8_lossy(&body?)).map_err(|e| format!("Invalid feed: {e}"))
+}
+
+/// Items seen on a feed
+#[derive(Default, Debug, Serialize, Deserialize)]
+struct SeenItems {
+    /// GUIDs of the last added items
+    guids: VecDeque<String>,
+    /// Episodes already added, for the episode deduplication
+    episodes: BTreeSet<String>,
+}
+
+/// Compiled filters of a feed
+#[derive(Debug)]
+struct FeedFilter {
+    filters: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl FeedFilter {
+    fn new(settings: &FeedSettings) -> Result<FeedFilter, regex::Error> {
+        Ok(FeedFilter {
+            filters: settings
+                .filters
+                .iter()
+                .map(|f| Regex::new(f))
+                .collect::<Result<_, _>>()?,
+            exclude: settings
+                .exclude
+                .iter()
+                .map(|f| Regex::new(f))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Method to know if an item title pass the filters
+    /// return `true` if it's the case
+    fn matches(&self, title: &str) -> bool {
+        (self.filters.is_empty() || self.filters.iter().any(|f| f.is_match(title)))
+            && !self.exclude.iter().any(|f| f.is_match(title))
+    }
+}
+
+/// State of the feeds: last polls, items seen and items submitted with `torrent-add` waiting for their result
+#[derive(Default, Debug)]
+pub struct Feeds {
+    /// Filters of the feeds, in the order of the settings
+    filters: Vec<FeedFilter>,
+    /// Path of the JSON file where seen items are remembered
+    state_path: Option<PathBuf>,
+    /// Seen items by feed name
+    seen: BTreeMap<String, SeenItems>,
+    /// Last poll of feeds by name
+    last_poll: HashMap<String, DateTime<Utc>>,
+    /// Submitted items with their feed name and episode, in the order of their `torrent-add` methods
+    pending: VecDeque<(String, FeedItem, Option<String>)>,
+}
+
+impl Feeds {
+    /// Compile the feed filters, and load the seen items from the state file
+    pub fn new(settings: &[FeedSettings], state_path: Option<PathBuf>) -> Result<Feeds, String> {
+        let filters = settings
+            .iter()
+            .map(|s| {
+                FeedFilter::new(s).map_err(|e| format!("Wrong filter for feed `{}`: {e}", s.name))
+            })
+            .collect::<Result<_, _>>()?;
+        let seen = match state_path.as_ref().map(std::fs::read) {
+            Some(Ok(state)) => serde_json::from_slice(&state).unwrap_or_else(|e| {
+                warn!("Can't load the feed state {state_path:?}, start from scratch: {e}");
+                BTreeMap::new()
+            }),
+            Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Can't read the feed state {state_path:?}, start from scratch: {e}");
+                BTreeMap::new()
+            }
+            _ => BTreeMap::new(),
+        };
+
+        Ok(Feeds {
+            filters,
+            state_path,
+            seen,
+            ..Default::default()
+        })
+    }
+
+    /// Poll the feeds that reached their interval, and return a `torrent-add` method for every new matching item
+    pub async fn poll(&mut self, settings: &[FeedSettings]) -> Vec<Method> {
+        // Items of a previous cycle still pending were not added, so they'll be found again
+        self.pending.clear();
+
+        let now = Utc::now();
+        let mut methods = Vec::new();
+        for (feed, filter) in settings.iter().zip(&self.filters) {
+            if self
+                .last_poll
+                .get(&feed.name)
+                .is_some_and(|last| now - *last < Duration::minutes(feed.interval as i64))
+            {
+                continue;
+            }
+            self.last_poll.insert(feed.name.clone(), now);
+
+            let items = match time::timeout(FEED_TIMEOUT, fetch_feed(&feed.target)).await {
+                Ok(Ok(items)) => items,
+                Ok(Err(e)) => {
+                    warn!("Can't poll the feed `{}`: {e}", feed.name);
+                    continue;
+                }
+                Err(_) => {
+                    warn!("Timeout to poll the feed `{}`", feed.name);
+                    continue;
+                }
+            };
+
+            let seen = self.seen.entry(feed.name.clone()).or_default();
+            for item in items {
+                if seen.guids.contains(&item.guid) || !filter.matches(&item.title) {
+                    continue;
+                }
+
+                let episode = if feed.episode_dedup {
+                    episode_key(&item.title)
+                } else {
+                    None
+                };
+                if let Some(episode) = &episode
+                    && (seen.episodes.contains(episode)
+                        || self
+                            .pending
+                            .iter()
+                            .any(|(n, _, e)| *n == feed.name && e.as_ref() == Some(episode)))
+                {
+                    continue;
+                }
+
+                methods.push(Method::TorrentAdd(Box::new(TorrentAddParams {
+                    download_dir: feed.download_dir.clone(),
+                    filename: Some(item.url.clone()),
+                    labels: (!feed.labels.is_empty()).then(|| feed.labels.clone()),
+                    paused: feed.paused.then_some(true),
+                    ..Default::default()
+                })));
+                self.pending.push_back((feed.name.clone(), item, episode));
+            }
+        }
+
+        methods
+    }
+
+    /// Process the response of the next `torrent-add` submitted from a feed, to remember the item if it was added
+    pub async fn process_response(&mut self, response: &Response) {
+        if let Some((name, item, episode)) = self.pending.pop_front() {
+            if !response.is_success() {
+                warn!(
+                    "Can't add `{}` of the feed `{name}`: {}",
+                    item.title, response.result
+                );
+                return;
+            }
+
+            if response.arguments.torrent_duplicate.is_some() {
+                info!(
+                    "`{}` of the feed `{name}` is already in Transmission",
+                    item.title
+                );
+            } else {
+                info!("`{}` of the feed `{name}` added", item.title);
+            }
+
+            let seen = self.seen.entry(name).or_default();
+            seen.guids.push_back(item.guid);
+            if seen.guids.len() > MAX_SEEN_ITEMS {
+                seen.guids.pop_front();
+            }
+            if let Some(episode) = episode {
+                seen.episodes.insert(episode);
+            }
+
+            if let Err(e) = self.save().await {
+                warn!("Can't write the feed state {:?}: {e}", self.state_path);
+            }
+        }
+    }
+
+    /// Write the seen items in the state file, through a temporary file to not corrupt it
+    async fn save(&self) -> std::io::Result<()> {
+        if let Some(path) = &self.state_path {
+            let tmp_path = path.with_extension("tmp");
+            tokio::fs::write(&tmp_path, serde_json::to_vec(&self.seen)?).await?;
+            tokio::fs::rename(tmp_path, path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{server::conn::http1 as server_http1, service::service_fn};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+          <channel>
+            <title>Tracker</title>
+            <item>
+              <title>Show.Name.S01E02.1080p.WEB.h264</title>
+              <guid isPermaLink="false">tracker-1002</guid>
+              <link>https://tracker.example/details/1002</link>
+              <enclosure url="https://tracker.example/download/1002.torrent" type="application/x-bittorrent" length="1024"/>
+            </item>
+            <item>
+              <title>Show Name S01E02 720p</title>
+              <guid>tracker-1001</guid>
+              <enclosure url="https://tracker.example/download/1001.torrent" type="application/x-bittorrent"/>
+            </item>
+            <item>
+              <title>Show.Name.S01E03.1080p.WEB.h264</title>
+              <link>magnet:?xt=urn:btih:a2b5d9e8f1c3b4a5968778695a4b3c2d1e0f9a8b</link>
+            </item>
+            <item>
+              <title>Other.Show.1x04.HDTV</title>
+              <guid>tracker-1004</guid>
+              <link>https://tracker.example/download/1004.torrent</link>
+            </item>
+            <item>
+              <title>Show.Name.S01E04.1080p.CAM</title>
+              <guid>tracker-1005</guid>
+              <link>https://tracker.example/download/1005.torrent</link>
+            </item>
+          </channel>
+        </rss>"#;
+
+    #[tokio::test]
+    async fn feeds() {
+        let atom = parse_feed(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom">
+                <entry>
+                  <title>Linux ISO</title>
+                  <id>urn:uuid:1225c695</id>
+                  <link href="https://example.org/iso"/>
+                  <link rel="enclosure" href="https://example.org/iso.torrent"/>
+                </entry>
+              </feed>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![FeedItem {
+                guid: "urn:uuid:1225c695".to_string(),
+                title: "Linux ISO".to_string(),
+                url: "https://example.org/iso.torrent".to_string(),
+            }],
+            atom
+        );
+        assert_eq!(
+            Some("show name s01e02"),
+            episode_key("Show.Name.S01E02.1080p").as_deref()
+        );
+        assert_eq!(
+            Some("other show s01e04"),
+            episode_key("Other Show - 1x04").as_deref()
+        );
+        assert_eq!(None, episode_key("Linux ISO 2024"));
+
+        // Local stand-in of the feed server
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(server_http1::Builder::new().serve_connection(
+                    TokioIo::new(stream),
+                    service_fn(|_| async {
+                        Ok::<_, std::convert::Infallible>(hyper::Response::new(Full::new(
+                            Bytes::from_static(RSS.as_bytes()),
+                        )))
+                    }),
+                ));
+            }
+        });
+
+        let state_path =
+            std::env::temp_dir().join(format!("prosa-ovserver-feeds-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&state_path);
+        let settings: Vec<FeedSettings> = serde_json::from_value(serde_json::json!([{
+            "name": "tracker",
+            "target": { "url": format!("http://{addr}/rss?passkey=secret") },
+            "filters": ["(?i)^show[ .]name"],
+            "exclude": ["(?i)\\bCAM\\b"],
+            "episode_dedup": true,
+            "labels": ["tv"],
+        }]))
+        .unwrap();
+        let mut feeds = Feeds::new(&settings, Some(state_path.clone())).unwrap();
+        let methods = feeds.poll(&settings).await;
+        let urls: Vec<_> = methods
+            .iter()
+            .filter_map(|m| match m {
+                Method::TorrentAdd(params) => params.filename.clone(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "https://tracker.example/download/1002.torrent",
+                "magnet:?xt=urn:btih:a2b5d9e8f1c3b4a5968778695a4b3c2d1e0f9a8b",
+            ],
+            urls
+        );
+
+        // The magnet failed, so only the first item is remembered
+        for response in [
+            r#"{"arguments":{"torrent-added":{"hashString":"0a1b","id":1,"name":"Show"}},"result":"success"}"#,
+            r#"{"arguments":{},"result":"invalid or corrupt torrent file"}"#,
+        ] {
+            feeds
+                .process_response(&serde_json::from_str(response).unwrap())
+                .await;
+        }
+        assert!(feeds.poll(&settings).await.is_empty());
+
+        // Seen items are loaded again, but the feed is polled on a new start
+        let mut feeds = Feeds::new(&settings, Some(state_path.clone())).unwrap();
+        assert_eq!(1, feeds.poll(&settings).await.len());
+
+        std::fs::remove_file(&state_path).unwrap();
+    }
+}
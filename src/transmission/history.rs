@@ -0,0 +1,409 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::transmission::api::{Id, Method, TorrentField, TorrentId, TorrentsArguments};
+
+/// Settings of the persistent history of torrents
+#[derive(Debug, Deserialize)]
+pub struct HistorySettings {
+    /// Path of the JSON file where the history is stored
+    pub path: PathBuf,
+    /// Amount of days the history of a removed torrent is kept
+    #[serde(default = "HistorySettings::default_retention")]
+    pub retention: u64,
+}
+
+impl HistorySettings {
+    fn default_retention() -> u64 {
+        90
+    }
+}
+
+/// Amount of bytes uploaded and downloaded
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub uploaded: u64,
+    pub downloaded: u64,
+}
+
+impl Transfer {
+    /// Getter of the transfer since a previous one.
+    /// If a counter went back (torrent added again), it's counted from zero
+    fn since(&self, previous: &Transfer) -> Transfer {
+        let delta = |current: u64, previous: u64| current.checked_sub(previous).unwrap_or(current);
+        Transfer {
+            uploaded: delta(self.uploaded, previous.uploaded),
+            downloaded: delta(self.downloaded, previous.downloaded),
+        }
+    }
+
+    fn add(&mut self, transfer: &Transfer) {
+        self.uploaded += transfer.uploaded;
+        self.downloaded += transfer.downloaded;
+    }
+}
+
+/// Getter of the tracker host of the first announce URL of a tracker list
+fn tracker_host(tracker_list: &str) -> Option<String> {
+    tracker_list
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())?
+        .parse::<http::Uri>()
+        .ok()?
+        .host()
+        .map(|h| h.to_lowercase())
+}
+
+/// History of a torrent, kept across restarts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TorrentHistory {
+    /// First time the torrent was seen on the daemon
+    pub first_seen: DateTime<Utc>,
+    /// Last time the torrent was seen on the daemon
+    pub last_seen: DateTime<Utc>,
+    /// Completion time of the download
+    pub completed: Option<DateTime<Utc>>,
+    /// Last time the torrent was seen active (upload or download)
+    pub last_active: Option<DateTime<Utc>>,
+    /// Highest upload ratio seen
+    pub peak_ratio: f64,
+    /// Count of mutations applied on the torrent
+    pub mutations: u64,
+    /// Count of recovery attempts (reannounce, verify) on the torrent
+    pub recovery_attempts: u64,
+    /// Time the torrent was found removed from the daemon
+    pub removed: Option<DateTime<Utc>>,
+    /// Last `uploadedEver` and `downloadedEver` seen, to account the transfer between fetches
+    #[serde(default)]
+    pub transfer: Transfer,
+}
+
+impl TorrentHistory {
+    fn new(now: DateTime<Utc>) -> TorrentHistory {
+        TorrentHistory {
+            first_seen: now,
+            last_seen: now,
+            completed: None,
+            last_active: None,
+            peak_ratio: 0.0,
+            mutations: 0,
+            recovery_attempts: 0,
+            removed: None,
+            transfer: Transfer::default(),
+        }
+    }
+}
+
+/// Content of the history file
+#[derive(Default, Debug, Serialize, Deserialize)]
+struct HistoryState {
+    /// Last session id of the daemon, to not negotiate it again on restart
+    session_id: Option<String>,
+    /// History of torrents by hash
+    torrents: BTreeMap<String, TorrentHistory>,
+    /// Total transfer by tracker host, including removed torrents
+    #[serde(default)]
+    trackers: BTreeMap<String, Transfer>,
+    /// Total transfer by label, including removed torrents
+    #[serde(default)]
+    labels: BTreeMap<String, Transfer>,
+}
+
+/// Persistent store of the torrents history, saved as a JSON file
+#[derive(Debug)]
+pub struct HistoryStore {
+    path: PathBuf,
+    retention: Duration,
+    state: HistoryState,
+    /// Hash of torrents by id, to attribute mutations
+    hashes: HashMap<Id, String>,
+}
+
+impl HistoryStore {
+    /// Fields to request on `torrent-get` to keep the history
+    pub const FIELDS: [TorrentField; 9] = [
+        TorrentField::HashString,
+        TorrentField::ActivityDate,
+        TorrentField::DoneDate,
+        TorrentField::PercentDone,
+        TorrentField::UploadRatio,
+        TorrentField::UploadedEver,
+        TorrentField::DownloadedEver,
+        TorrentField::TrackerList,
+        TorrentField::Labels,
+    ];
+
+    /// Open the store, and load the history file if it exists
+    pub fn open(settings: &HistorySettings) -> HistoryStore {
+        let state = match std::fs::read(&settings.path) {
+            Ok(state) => serde_json::from_slice(&state).unwrap_or_else(|e| {
+                warn!(
+                    "Can't load the history {:?}, start from scratch: {e}",
+                    settings.path
+                );
+                HistoryState::default()
+            }),
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                warn!(
+                    "Can't read the history {:?}, start from scratch: {e}",
+                    settings.path
+                );
+                HistoryState::default()
+            }
+            Err(_) => HistoryState::default(),
+        };
+
+        HistoryStore {
+            path: settings.path.clone(),
+            retention: Duration::days(settings.retention as i64),
+            state,
+            hashes: HashMap::new(),
+        }
+    }
+
+    /// Getter of the last known session id of the daemon
+    pub fn session_id(&self) -> Option<&str> {
+        self.state.session_id.as_deref()
+    }
+
+    /// Setter of the session id of the daemon
+    pub fn set_session_id(&mut self, session_id: Option<String>) {
+        self.state.session_id = session_id;
+    }
+
+    /// Getter of the history of a torrent from its hash
+    pub fn get(&self, hash: &str) -> Option<&TorrentHistory> {
+        self.state.torrents.get(&hash.to_lowercase())
+    }
+
+    /// Getter of the total transfer by tracker host
+    pub fn tracker_transfer(&self) -> &BTreeMap<String, Transfer> {
+        &self.state.trackers
+    }
+
+    /// Getter of the total transfer by label
+    pub fn label_transfer(&self) -> &BTreeMap<String, Transfer> {
+        &self.state.labels
+    }
+
+    /// Update the history from the whole torrent table of the daemon, and account the transfer since the last update by tracker and label.
+    /// Torrents no longer in the table are marked as removed, and forgotten after the retention period
+    pub fn update<'a>(
+        &mut self,
+        torrents: impl Iterator<Item = (&'a Id, &'a TorrentsArguments)>,
+        now: DateTime<Utc>,
+    ) {
+        self.hashes.clear();
+        for (id, torrent) in torrents {
+            let Some(hash) = torrent.hash_string.as_ref().map(|h| h.to_lowercase()) else {
+                continue;
+            };
+
+            let history = self
+                .state
+                .torrents
+                .entry(hash.clone())
+                .or_insert_with(|| TorrentHistory::new(now));
+            history.last_seen = now;
+            history.removed = None;
+            if history.completed.is_none() {
+                history.completed = torrent.done_date.filter(|d| d.timestamp() > 0).or_else(|| {
+                    torrent
+                        .percent_done
+                        .is_some_and(|p| p >= 1.0)
+                        .then_some(now)
+                });
+            }
+            if let Some(activity_date) = torrent.activity_date
+                && activity_date.timestamp() > 0
+                && history.last_active.is_none_or(|d| d < activity_date)
+            {
+                history.last_active = Some(activity_date);
+            }
+            if let Some(ratio) = torrent.upload_ratio
+                && ratio > history.peak_ratio
+            {
+                history.peak_ratio = ratio;
+            }
+
+            let transfer = Transfer {
+                uploaded: torrent.uploaded_ever.unwrap_or(history.transfer.uploaded),
+                downloaded: torrent
+                    .downloaded_ever
+                    .unwrap_or(history.transfer.downloaded),
+            };
+            let delta = transfer.since(&history.transfer);
+            history.transfer = transfer;
+            if delta != Transfer::default() {
+                if let Some(tracker) = torrent.tracker_list.as_deref().and_then(tracker_host) {
+                    self.state.trackers.entry(tracker).or_default().add(&delta);
+                }
+                for label in torrent.labels.iter().flatten() {
+                    self.state
+                        .labels
+                        .entry(label.clone())
+                        .or_default()
+                        .add(&delta);
+                }
+            }
+
+            self.hashes.insert(id.clone(), hash);
+        }
+
+        for history in self.state.torrents.values_mut() {
+            if history.last_seen < now && history.removed.is_none() {
+                history.removed = Some(now);
+            }
+        }
+        self.state
+            .torrents
+            .retain(|_, h| h.removed.is_none_or(|d| now - d < self.retention));
+    }
+
+    /// Record a mutation successfully applied on torrents
+    pub fn record_mutation(&mut self, method: &Method) {
+        let (ids, recovery) = match method {
+            Method::TorrentStart(ids) | Method::TorrentStartNow(ids) | Method::TorrentStop(ids) => {
+                (ids.iter().cloned().collect(), false)
+            }
+            Method::TorrentVerify(ids) | Method::TorrentReannounce(ids) => {
+                (ids.iter().cloned().collect(), true)
+            }
+            Method::TorrentSet(params) => (params.ids.clone().unwrap_or_default(), false),
+            Method::TorrentRemove(ids, _) => (ids.clone(), false),
+            _ => (Vec::new(), false),
+        };
+
+        for id in ids.into_iter().flat_map(|id| match id {
+            TorrentId::Id(id) => vec![id],
+            TorrentId::List(ids) => ids,
+            TorrentId::RecentlyActive => Vec::new(),
+        }) {
+            let hash = match id {
+                Id::Hash(hash) => hash.to_lowercase(),
+                id => match self.hashes.get(&id) {
+                    Some(hash) => hash.clone(),
+                    None => continue,
+                },
+            };
+            if let Some(history) = self.state.torrents.get_mut(&hash) {
+                history.mutations += 1;
+                if recovery {
+                    history.recovery_attempts += 1;
+                }
+            }
+        }
+    }
+
+    /// Write the history file, through a temporary file to not corrupt it
+    pub async fn save(&self) -> std::io::Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_vec(&self.state)?).await?;
+        tokio::fs::rename(tmp_path, &self.path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transmission::api::TorrentSetParams;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn history_store() {
+        let settings = HistorySettings {
+            path: std::env::temp_dir().join(format!(
+                "prosa-ovserver-history-{}.json",
+                std::process::id()
+            )),
+            retention: 30,
+        };
+        let _ = std::fs::remove_file(&settings.path);
+
+        let mut torrents: HashMap<Id, TorrentsArguments> = [
+            (
+                Id::Id(1),
+                r#"{ "hashString": "0A1B", "percentDone": 1.0, "doneDate": 1700000000, "activityDate": 1700000100, "uploadRatio": 2.5,
+                    "uploadedEver": 1000, "downloadedEver": 400, "labels": ["tv"],
+                    "trackerList": "https://Private.example/announce?passkey=secret\n\nudp://backup.example:6969/announce" }"#,
+            ),
+            (
+                Id::Id(2),
+                r#"{ "hashString": "a2b5", "percentDone": 0.5, "doneDate": 0, "uploadRatio": 0.1,
+                    "uploadedEver": 10, "downloadedEver": 200, "labels": ["tv", "iso"],
+                    "trackerList": "udp://public.example:1337/announce" }"#,
+            ),
+        ]
+        .into_iter()
+        .map(|(id, torrent)| (id, serde_json::from_str(torrent).unwrap()))
+        .collect();
+
+        let first_seen = Utc::now() - Duration::days(1);
+        let mut store = HistoryStore::open(&settings);
+        store.update(torrents.iter(), first_seen);
+        store.record_mutation(&Method::TorrentReannounce(Some(TorrentId::List(vec![
+            Id::Id(1),
+            Id::Id(3),
+        ]))));
+        store.record_mutation(&Method::TorrentSet(Box::new(TorrentSetParams {
+            ids: Some(vec![TorrentId::Id(Id::Hash("A2B5".to_string()))]),
+            ..Default::default()
+        })));
+        store.set_session_id(Some("session-1".to_string()));
+        store.save().await.unwrap();
+
+        // The history is loaded again on restart
+        let mut store = HistoryStore::open(&settings);
+        assert_eq!(Some("session-1"), store.session_id());
+        let history = store.get("0a1b").unwrap();
+        assert_eq!(first_seen, history.first_seen);
+        assert_eq!(Some(1700000000), history.completed.map(|d| d.timestamp()));
+        assert_eq!(Some(1700000100), history.last_active.map(|d| d.timestamp()));
+        assert_eq!(2.5, history.peak_ratio);
+        assert_eq!((1, 1), (history.mutations, history.recovery_attempts));
+        let history = store.get("A2B5").unwrap();
+        assert_eq!(None, history.completed);
+        assert_eq!((1, 0), (history.mutations, history.recovery_attempts));
+
+        // The peak ratio is kept, and the removed torrent is forgotten after the retention
+        torrents.get_mut(&Id::Id(1)).unwrap().upload_ratio = Some(1.0);
+        torrents.get_mut(&Id::Id(1)).unwrap().uploaded_ever = Some(1500);
+        torrents.remove(&Id::Id(2));
+        let now = Utc::now();
+        store.update(torrents.iter(), now);
+        assert_eq!(2.5, store.get("0a1b").unwrap().peak_ratio);
+        assert_eq!(Some(now), store.get("a2b5").unwrap().removed);
+        store.update(torrents.iter(), now + Duration::days(31));
+        assert_eq!(first_seen, store.get("0a1b").unwrap().first_seen);
+        assert_eq!(None, store.get("a2b5"));
+
+        // Transfers are kept by tracker and label, including the ones of removed torrents
+        let transfer = |uploaded, downloaded| Transfer {
+            uploaded,
+            downloaded,
+        };
+        assert_eq!(
+            BTreeMap::from([
+                ("private.example".to_string(), transfer(1500, 400)),
+                ("public.example".to_string(), transfer(10, 200)),
+            ]),
+            *store.tracker_transfer()
+        );
+        assert_eq!(
+            BTreeMap::from([
+                ("iso".to_string(), transfer(10, 200)),
+                ("tv".to_string(), transfer(1510, 600)),
+            ]),
+            *store.label_transfer()
+        );
+
+        std::fs::remove_file(&settings.path).unwrap();
+    }
+}
@@ -0,0 +1,404 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use http::{Request, Response, StatusCode};
+use http_body_util::{BodyExt as _, Full};
+use hyper::{body::Incoming, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use serde_json::{Map, Value, json};
+use tokio::net::TcpListener;
+use tracing::debug;
+
+use super::api::{JSON_RPC_MIN_VERSION, RpcDialect, legacy_key, map_keys, snake_key};
+
+/// Failure to return on the next request of the mock
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockFailure {
+    /// Respond with an HTTP status code
+    Http(StatusCode),
+    /// Respond with a `result` string other than `success`
+    Result(String),
+    /// Respond with a raw body, like a truncated or malformed JSON
+    Body(String),
+}
+
+/// State of the mock shared with the server task
+#[derive(Debug, Default)]
+struct MockState {
+    torrents: Vec<Map<String, Value>>,
+    /// Ids of removed torrents, not yet returned by a `recently-active` request
+    removed: Vec<Value>,
+    session_id: u64,
+    /// `rpc-version` returned by `session-get`
+    rpc_version: u64,
+    failures: VecDeque<MockFailure>,
+    /// Failures for the next request of a method
+    method_failures: HashMap<String, MockFailure>,
+    requests: Vec<Value>,
+    uploaded_bytes: u64,
+    downloaded_bytes: u64,
+}
+
+impl MockState {
+    fn session_id(&self) -> String {
+        format!("mock-session-{}", self.session_id)
+    }
+
+    /// Getter of the index of torrents selected by the `ids` argument
+    fn select(&self, ids: Option<&Value>) -> Vec<usize> {
+        let matches = |torrent: &Map<String, Value>, id: &Value| {
+            torrent.get("id") == Some(id) || torrent.get("hashString") == Some(id)
+        };
+        let selected = |torrent: &Map<String, Value>| match ids {
+            None => true,
+            Some(Value::String(ids)) if ids == "recently-active" => true,
+            Some(Value::Array(ids)) => ids.iter().any(|id| matches(torrent, id)),
+            Some(id) => matches(torrent, id),
+        };
+        self.torrents
+            .iter()
+            .enumerate()
+            .filter(|(_, torrent)| selected(torrent))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Process a legacy RPC request, and return the `arguments` of the response
+    fn process(&mut self, method: &str, arguments: &Map<String, Value>) -> Value {
+        match method {
+            "session-get" => json!({
+                "rpc-version": self.rpc_version,
+                "rpc-version-minimum": 14,
+                "version": if self.rpc_version >= JSON_RPC_MIN_VERSION { "4.1.0 (mock)" } else { "4.0.6 (mock)" },
+            }),
+            "session-stats" => json!({
+                "activeTorrentCount": self.torrents.len(),
+                "pausedTorrentCount": 0,
+                "torrentCount": self.torrents.len(),
+                "downloadSpeed": 0,
+                "uploadSpeed": 0,
+                "cumulative-stats": {
+                    "uploadedBytes": self.uploaded_bytes,
+                    "downloadedBytes": self.downloaded_bytes,
+                    "filesAdded": self.torrents.len(),
+                    "secondsActive": 3600,
+                    "sessionCount": 1,
+                },
+                "current-stats": {
+                    "uploadedBytes": self.uploaded_bytes,
+                    "downloadedBytes": self.downloaded_bytes,
+                    "filesAdded": self.torrents.len(),
+                    "secondsActive": 3600,
+                    "sessionCount": 1,
+                },
+            }),
+            "torrent-get" => {
+                let fields: Vec<&str> = arguments
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .map(|f| f.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default();
+                let selected = self.select(arguments.get("ids"));
+                let torrents = selected.into_iter().map(|i| {
+                    fields
+                        .iter()
+                        .filter_map(|f| Some((f.to_string(), self.torrents[i].get(*f)?.clone())))
+                        .collect::<Map<String, Value>>()
+                });
+
+                let mut response =
+                    if arguments.get("format").and_then(Value::as_str) == Some("table") {
+                        let mut rows = vec![json!(fields)];
+                        rows.extend(torrents.map(|t| {
+                            Value::Array(
+                                fields
+                                    .iter()
+                                    .map(|f| t.get(*f).cloned().unwrap_or(Value::Null))
+                                    .collect(),
+                            )
+                        }));
+                        json!({ "torrents": rows })
+                    } else {
+                        json!({ "torrents": torrents.collect::<Vec<_>>() })
+                    };
+                if arguments.get("ids").and_then(Value::as_str) == Some("recently-active") {
+                    response["removed"] = Value::Array(std::mem::take(&mut self.removed));
+                }
+                response
+            }
+            "torrent-set" => {
+                for i in self.select(arguments.get("ids")) {
+                    for (key, value) in arguments.iter().filter(|(k, _)| *k != "ids") {
+                        let key = if key == "location" {
+                            "downloadDir"
+                        } else {
+                            key
+                        };
+                        self.torrents[i].insert(key.to_string(), value.clone());
+                    }
+                }
+                json!({})
+            }
+            "torrent-start" | "torrent-start-now" | "torrent-stop" => {
+                for i in self.select(arguments.get("ids")) {
+                    let torrent = &mut self.torrents[i];
+                    let status = if method == "torrent-stop" {
+                        0
+                    } else if torrent.get("percentDone").and_then(Value::as_f64) == Some(1.0) {
+                        6
+                    } else {
+                        4
+                    };
+                    torrent.insert("status".to_string(), status.into());
+                }
+                json!({})
+            }
+            "torrent-remove" => {
+                let mut selected = self.select(arguments.get("ids"));
+                selected.sort_unstable();
+                for i in selected.into_iter().rev() {
+                    let torrent = self.torrents.remove(i);
+                    if let Some(id) = torrent.get("id") {
+                        self.removed.push(id.clone());
+                    }
+                }
+                json!({})
+            }
+            _ => json!({}),
+        }
+    }
+}
+
+/// In-process mock of a Transmission daemon over an in-memory torrent table.
+/// It responds in the dialect of each request, and advertises the JSON-RPC 2.0 dialect if it's set with [`MockTransmission::with_dialect`].
+///
+/// It implements the session id handshake (409 with `X-Transmission-Session-Id`), `session-get`, `session-stats`,
+/// `torrent-get` (objects and table format, `recently-active`), `torrent-set`, `torrent-start`, `torrent-stop` and `torrent-remove`.
+/// Tests can rotate the session id, and script failures of the next requests.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransmission {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransmission {
+    /// Create a mock with torrents given as legacy `torrent-get` objects (camelCase keys)
+    pub fn new(torrents: Vec<Value>) -> MockTransmission {
+        MockTransmission {
+            state: Arc::new(Mutex::new(MockState {
+                torrents: torrents
+                    .into_iter()
+                    .filter_map(|t| match t {
+                        Value::Object(torrent) => Some(torrent),
+                        _ => None,
+                    })
+                    .collect(),
+                session_id: 1,
+                rpc_version: 17,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Set the dialect advertised by `session-get`, like Transmission 4.0 (legacy) or 4.1 (JSON-RPC 2.0)
+    pub fn with_dialect(self, dialect: RpcDialect) -> MockTransmission {
+        self.state.lock().unwrap().rpc_version = match dialect {
+            RpcDialect::Legacy => 17,
+            RpcDialect::JsonRpc => JSON_RPC_MIN_VERSION,
+        };
+        self
+    }
+
+    /// Start to serve the mock on a local port, and return its address
+    pub async fn serve(&self) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let mock = self.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let mock = mock.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(|request| {
+                        let mock = mock.clone();
+                        async move { mock.handle(request).await }
+                    });
+                    if let Err(e) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        debug!("Mock Transmission connection error: {e}");
+                    }
+                });
+            }
+        });
+        Ok(addr)
+    }
+
+    /// Change the session id, so the next request get a 409 response
+    pub fn rotate_session_id(&self) {
+        self.state.lock().unwrap().session_id += 1;
+    }
+
+    /// Script a failure for the next request. Failures are returned in order
+    pub fn fail_next(&self, failure: MockFailure) {
+        self.state.lock().unwrap().failures.push_back(failure);
+    }
+
+    /// Script a failure for the next request of a method
+    pub fn fail_on(&self, method: &str, failure: MockFailure) {
+        self.state
+            .lock()
+            .unwrap()
+            .method_failures
+            .insert(method.to_string(), failure);
+    }
+
+    /// Set the cumulative upload and download of the session
+    pub fn set_transfer(&self, uploaded_bytes: u64, downloaded_bytes: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.uploaded_bytes = uploaded_bytes;
+        state.downloaded_bytes = downloaded_bytes;
+    }
+
+    /// Getter of the current torrent table
+    pub fn torrents(&self) -> Vec<Value> {
+        self.state
+            .lock()
+            .unwrap()
+            .torrents
+            .iter()
+            .cloned()
+            .map(Value::Object)
+            .collect()
+    }
+
+    /// Getter of all the requests processed by the mock (with a valid session id)
+    pub fn requests(&self) -> Vec<Value> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Getter of the method names of the processed requests, as they were sent in their dialect
+    pub fn methods(&self) -> Vec<String> {
+        self.requests()
+            .iter()
+            .filter_map(|r| r.get("method")?.as_str().map(String::from))
+            .collect()
+    }
+
+    async fn handle(
+        &self,
+        request: Request<Incoming>,
+    ) -> Result<Response<Full<Bytes>>, Infallible> {
+        let session_id = request
+            .headers()
+            .get("x-transmission-session-id")
+            .and_then(|s| s.to_str().ok())
+            .map(String::from);
+        let body = request
+            .into_body()
+            .collect()
+            .await
+            .map(|b| b.to_bytes())
+            .unwrap_or_default();
+
+        let mut state = self.state.lock().unwrap();
+        let response = Response::builder().header("X-Transmission-Session-Id", state.session_id());
+        if session_id.as_deref() != Some(state.session_id().as_str()) {
+            return Ok(response
+                .status(StatusCode::CONFLICT)
+                .body(Full::new(Bytes::from_static(
+                    b"<h1>409: Conflict</h1><p>Your request had an invalid session-id header.</p>",
+                )))
+                .unwrap());
+        }
+
+        let Ok(request) = serde_json::from_slice::<Value>(&body) else {
+            return Ok(response
+                .status(StatusCode::BAD_REQUEST)
+                .body(Full::default())
+                .unwrap());
+        };
+        state.requests.push(request.clone());
+
+        // Requests of the JSON-RPC dialect are processed with their legacy names
+        let json_rpc = request.get("jsonrpc").is_some();
+        let tag = request.get(if json_rpc { "id" } else { "tag" }).cloned();
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .replace('_', "-");
+        let failure = match state.method_failures.remove(&method) {
+            Some(failure) => Some(failure),
+            None => state.failures.pop_front(),
+        };
+        let mut result = match failure {
+            Some(MockFailure::Http(status)) => {
+                return Ok(response.status(status).body(Full::default()).unwrap());
+            }
+            Some(MockFailure::Body(body)) => {
+                return Ok(response
+                    .status(StatusCode::OK)
+                    .header(hyper::header::CONTENT_TYPE, "application/json")
+                    .body(Full::new(Bytes::from(body)))
+                    .unwrap());
+            }
+            Some(MockFailure::Result(result)) if json_rpc => {
+                json!({ "jsonrpc": "2.0", "error": { "code": -32000, "message": result } })
+            }
+            Some(MockFailure::Result(result)) => json!({ "arguments": {}, "result": result }),
+            None if json_rpc => {
+                let mut arguments = request.get("params").cloned().unwrap_or(json!({}));
+                map_keys(&mut arguments, &legacy_key);
+                if let Some(Value::Array(fields)) = arguments.get_mut("fields") {
+                    for field in fields {
+                        if let Value::String(name) = field {
+                            *name = legacy_key(name).into_owned();
+                        }
+                    }
+                }
+                if arguments.get("ids").and_then(Value::as_str) == Some("recently_active") {
+                    arguments["ids"] = json!("recently-active");
+                }
+
+                let mut result =
+                    state.process(&method, arguments.as_object().unwrap_or(&Map::new()));
+                // The header of the table format holds field names too
+                if let Some(Value::Array(header)) = result
+                    .get_mut("torrents")
+                    .and_then(|t| t.get_mut(0))
+                    .filter(|h| h.is_array())
+                {
+                    for field in header {
+                        if let Value::String(name) = field {
+                            *name = snake_key(name).into_owned();
+                        }
+                    }
+                }
+                map_keys(&mut result, &snake_key);
+                json!({ "jsonrpc": "2.0", "result": result })
+            }
+            None => {
+                let arguments = request
+                    .get("arguments")
+                    .and_then(Value::as_object)
+                    .cloned()
+                    .unwrap_or_default();
+                json!({ "arguments": state.process(&method, &arguments), "result": "success" })
+            }
+        };
+        if let Some(tag) = tag {
+            result[if json_rpc { "id" } else { "tag" }] = tag;
+        }
+
+        Ok(response
+            .status(StatusCode::OK)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(result.to_string())))
+            .unwrap())
+    }
+}
@@ -5,3 +5,49 @@ pub mod adaptor;
 
 /// Module for [RPC API](https://github.com/transmission/transmission/blob/main/docs/rpc-spec.md)
 pub mod api;
+
+/// Policy to ban leechers and fake clients
+pub mod ban;
+
+/// Content model of a torrent (files, pieces)
+pub mod content;
+
+/// Cross-seeding detection of the same content on several torrents
+pub mod cross_seed;
+
+/// Policy to delete local data of removed torrents
+pub mod deletion;
+
+/// RSS and Atom feeds auto-downloader
+pub mod feed;
+
+/// Persistent history of torrents, kept across restarts
+pub mod history;
+
+/// Seeding obligations of private trackers
+pub mod obligation;
+
+/// Optimizer of the seeding torrents for ratio building
+pub mod optimizer;
+
+/// Notification sinks of torrent events (webhook, ntfy, Gotify, SMTP)
+pub mod notify;
+
+/// In-process mock of the Transmission RPC, for tests
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+
+/// ProSA service to drive Transmission with TVF messages
+pub mod service;
+
+/// Rules to select files to download
+pub mod rules;
+
+/// Torrent lifecycle events
+pub mod event;
+
+/// Watch folders to add torrents from `.torrent` and magnet files
+pub mod watch;
+
+/// TVF encoding of the RPC API types
+pub mod tvf;
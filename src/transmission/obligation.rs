@@ -0,0 +1,186 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::transmission::api::{Status, TorrentField, TorrentsArguments};
+
+/// Seeding obligation of a private tracker (hit-and-run rules).
+/// Ex: seed 72h or ratio 1.0 within 14 days
+#[derive(Debug, Deserialize)]
+pub struct SeedObligation {
+    /// Tracker of the obligation (part of the announce URL)
+    pub tracker: String,
+    /// Minimum seed time in hours
+    pub seed_time: Option<u64>,
+    /// Minimum upload ratio
+    pub ratio: Option<f64>,
+    /// Amount of days after the download completion to fulfill the obligation.
+    /// Without it, the obligation never expire
+    pub within: Option<u64>,
+}
+
+/// State of a torrent regarding seeding obligations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObligationState {
+    /// The torrent doesn't owe anything
+    Free,
+    /// The torrent still owe seed time or ratio
+    Owed,
+    /// The torrent still owe seed time or ratio, but it's stopped or stalled
+    AtRisk,
+}
+
+impl SeedObligation {
+    /// Fields to request on `torrent-get` to track obligations
+    pub const FIELDS: [TorrentField; 6] = [
+        TorrentField::TrackerList,
+        TorrentField::SecondsSeeding,
+        TorrentField::UploadRatio,
+        TorrentField::DoneDate,
+        TorrentField::Status,
+        TorrentField::IsStalled,
+    ];
+
+    /// Check that the obligation can be fulfilled, with a seed time or a ratio
+    pub fn check(&self) -> Result<(), String> {
+        if self.seed_time.is_none() && self.ratio.is_none() {
+            Err(format!(
+                "obligation of `{}` need a `seed_time` or a `ratio`",
+                self.tracker
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Method to know if the obligation apply to the torrent
+    fn applies(&self, torrent: &TorrentsArguments) -> bool {
+        torrent
+            .tracker_list
+            .as_ref()
+            .is_some_and(|tracker_list| tracker_list.contains(self.tracker.as_str()))
+    }
+
+    /// Method to know if the torrent seeded enough time or reached the ratio
+    fn fulfilled(&self, torrent: &TorrentsArguments) -> bool {
+        self.seed_time.is_some_and(|hours| {
+            torrent
+                .seconds_seeding
+                .is_some_and(|s| s >= Duration::hours(hours as i64))
+        }) || self
+            .ratio
+            .is_some_and(|ratio| torrent.upload_ratio.is_some_and(|r| r >= ratio))
+    }
+
+    /// Method to know if the delay to fulfill the obligation is over
+    fn expired(&self, torrent: &TorrentsArguments, now: DateTime<Utc>) -> bool {
+        // An unset done date is 0
+        match (self.within, torrent.done_date.filter(|d| d.timestamp() > 0)) {
+            (Some(days), Some(done_date)) => now - done_date >= Duration::days(days as i64),
+            _ => false,
+        }
+    }
+
+    /// Method to know if the torrent still owe seed time or ratio to the tracker
+    pub fn owed(&self, torrent: &TorrentsArguments, now: DateTime<Utc>) -> bool {
+        self.applies(torrent) && !self.fulfilled(torrent) && !self.expired(torrent, now)
+    }
+}
+
+impl ObligationState {
+    /// Compute the state of the torrent regarding all the obligations
+    pub fn from_torrent(
+        obligations: &[SeedObligation],
+        torrent: &TorrentsArguments,
+        now: DateTime<Utc>,
+    ) -> ObligationState {
+        if !obligations.iter().any(|o| o.owed(torrent, now)) {
+            ObligationState::Free
+        } else if torrent.status == Some(Status::Stopped) || torrent.is_stalled {
+            ObligationState::AtRisk
+        } else {
+            ObligationState::Owed
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_obligation() {
+        let obligations = [SeedObligation {
+            tracker: "private.example".to_string(),
+            seed_time: Some(72),
+            ratio: Some(1.0),
+            within: Some(14),
+        }];
+        assert!(obligations[0].check().is_ok());
+        assert!(
+            SeedObligation {
+                tracker: "private.example".to_string(),
+                seed_time: None,
+                ratio: None,
+                within: Some(14),
+            }
+            .check()
+            .is_err()
+        );
+
+        let now = Utc::now();
+        let torrent = |json: &str| -> TorrentsArguments { serde_json::from_str(json).unwrap() };
+        let done_date = (now - Duration::days(2)).timestamp();
+
+        assert_eq!(
+            ObligationState::Owed,
+            ObligationState::from_torrent(
+                &obligations,
+                &torrent(&format!(
+                    r#"{{ "trackerList": "https://private.example/announce", "secondsSeeding": 3600, "uploadRatio": 0.2, "doneDate": {done_date}, "status": 6 }}"#
+                )),
+                now
+            )
+        );
+        assert_eq!(
+            ObligationState::AtRisk,
+            ObligationState::from_torrent(
+                &obligations,
+                &torrent(&format!(
+                    r#"{{ "trackerList": "https://private.example/announce", "secondsSeeding": 3600, "uploadRatio": 0.2, "doneDate": {done_date}, "status": 0 }}"#
+                )),
+                now
+            )
+        );
+        // Ratio reached
+        assert_eq!(
+            ObligationState::Free,
+            ObligationState::from_torrent(
+                &obligations,
+                &torrent(&format!(
+                    r#"{{ "trackerList": "https://private.example/announce", "secondsSeeding": 3600, "uploadRatio": 1.5, "doneDate": {done_date}, "status": 0 }}"#
+                )),
+                now
+            )
+        );
+        // Seed time reached
+        assert_eq!(
+            ObligationState::Free,
+            ObligationState::from_torrent(
+                &obligations,
+                &torrent(&format!(
+                    r#"{{ "trackerList": "https://private.example/announce", "secondsSeeding": 260000, "uploadRatio": 0.2, "doneDate": {done_date}, "status": 0 }}"#
+                )),
+                now
+            )
+        );
+        // Other tracker
+        assert_eq!(
+            ObligationState::Free,
+            ObligationState::from_torrent(
+                &obligations,
+                &torrent(r#"{ "trackerList": "https://public.example/announce", "status": 0 }"#),
+                now
+            )
+        );
+    }
+}
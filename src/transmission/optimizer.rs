@@ -0,0 +1,137 @@
+use std::cmp::Ordering;
+
+use serde::Deserialize;
+
+use crate::transmission::api::{Id, Status, TorrentField, TorrentsArguments};
+
+/// Settings of the seeding optimizer, that keep seeding the most useful torrents for ratio building
+#[derive(Debug, Deserialize)]
+pub struct SeedOptimizerSettings {
+    /// Maximum number of torrents seeding at the same time
+    pub slots: usize,
+    /// Global upload budget in B/s. Once the upload rate of seeding torrents reach it, other torrents are stopped
+    pub upload_budget: Option<u64>,
+}
+
+/// Complete torrent that can be seeded
+#[derive(Debug, Clone)]
+pub struct SeedCandidate {
+    /// Id of the torrent
+    pub id: Id,
+    /// Usefulness of the torrent for ratio building
+    pub score: f64,
+    /// Upload rate in B/s
+    pub rate_upload: u64,
+    /// Indicate if the torrent is currently seeding
+    pub seeding: bool,
+    /// Indicate if the torrent must seed (ex: seeding obligation)
+    pub required: bool,
+}
+
+impl SeedCandidate {
+    /// Build a seed candidate from a complete torrent
+    pub fn from_torrent(id: Id, torrent: &TorrentsArguments, required: bool) -> SeedCandidate {
+        SeedCandidate {
+            id,
+            score: SeedOptimizerSettings::score(torrent),
+            rate_upload: torrent.rate_upload.unwrap_or_default().max(0) as u64,
+            seeding: matches!(torrent.status, Some(Status::Seed | Status::SeedWait)),
+            required,
+        }
+    }
+}
+
+impl SeedOptimizerSettings {
+    /// Fields to request on `torrent-get` to optimize seeding
+    pub const FIELDS: [TorrentField; 4] = [
+        TorrentField::Status,
+        TorrentField::PercentDone,
+        TorrentField::RateUpload,
+        TorrentField::TrackerStats,
+    ];
+
+    /// Score of a torrent for ratio building: the best leecher/seeder ratio of its trackers
+    pub fn score(torrent: &TorrentsArguments) -> f64 {
+        torrent
+            .tracker_stats
+            .iter()
+            .flatten()
+            .map(|stat| stat.leecher_count.max(0) as f64 / (stat.seeder_count.max(0) + 1) as f64)
+            .fold(0.0, f64::max)
+    }
+
+    /// Rank the candidates, and select the ones that should seed.
+    /// Return the torrents to start and the torrents to stop
+    pub fn optimize(&self, mut candidates: Vec<SeedCandidate>) -> (Vec<Id>, Vec<Id>) {
+        candidates.sort_by(|a, b| {
+            b.required
+                .cmp(&a.required)
+                .then(b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal))
+                .then(b.rate_upload.cmp(&a.rate_upload))
+        });
+
+        let mut start = Vec::new();
+        let mut stop = Vec::new();
+        let mut slots = 0;
+        let mut upload = 0;
+        for candidate in candidates {
+            let selected = candidate.required
+                || (slots < self.slots && self.upload_budget.is_none_or(|b| upload < b));
+            if selected {
+                slots += 1;
+                upload += candidate.rate_upload;
+                if !candidate.seeding {
+                    start.push(candidate.id);
+                }
+            } else if candidate.seeding {
+                stop.push(candidate.id);
+            }
+        }
+
+        (start, stop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: i64, score: f64, rate_upload: u64, seeding: bool) -> SeedCandidate {
+        SeedCandidate {
+            id: Id::Id(id),
+            score,
+            rate_upload,
+            seeding,
+            required: false,
+        }
+    }
+
+    #[test]
+    fn seed_optimizer() {
+        let torrent: TorrentsArguments = serde_json::from_str(
+            r#"{ "trackerStats": [
+                { "announce": "a", "announceState": 1, "downloadCount": 0, "downloader_count": 0, "host": "a", "id": 0, "leecherCount": 10, "scrape": "", "scrapeState": 1, "seederCount": 4, "sitename": "a", "tier": 0 },
+                { "announce": "b", "announceState": 1, "downloadCount": 0, "downloader_count": 0, "host": "b", "id": 1, "leecherCount": -1, "scrape": "", "scrapeState": 1, "seederCount": -1, "sitename": "b", "tier": 1 }
+            ] }"#,
+        )
+        .unwrap();
+        assert_eq!(2.0, SeedOptimizerSettings::score(&torrent));
+
+        let settings = SeedOptimizerSettings {
+            slots: 4,
+            upload_budget: Some(1000),
+        };
+        let mut required = candidate(5, 0.0, 0, false);
+        required.required = true;
+        let (start, stop) = settings.optimize(vec![
+            candidate(1, 0.5, 100, true),
+            candidate(2, 3.0, 0, false),
+            candidate(3, 1.0, 2000, true),
+            candidate(4, 0.1, 0, true),
+            required,
+        ]);
+        // Torrent 5 must seed, then the best torrents until the upload budget is reached by torrent 3
+        assert_eq!(vec![Id::Id(5), Id::Id(2)], start);
+        assert_eq!(vec![Id::Id(1), Id::Id(4)], stop);
+    }
+}
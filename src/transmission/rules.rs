@@ -0,0 +1,184 @@
+use glob::{MatchOptions, Pattern, PatternError};
+use serde::Deserialize;
+
+use crate::transmission::{api::TorrentSetParams, content::TorrentContent};
+
+/// Extensions of video files
+const VIDEO_EXTENSIONS: [&str; 9] = [
+    "mkv", "mp4", "m4v", "avi", "mov", "wmv", "webm", "ts", "mpg",
+];
+
+/// Options used to match file path with glob patterns
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Settings of the rules applied to files of newly added torrents
+#[derive(Default, Debug, Deserialize)]
+pub struct FileRulesSettings {
+    /// Glob patterns of files to not download (ex: `*.nfo`, `Sample/*`).
+    /// Patterns are matched against the end of the file path
+    #[serde(default)]
+    pub unwanted: Vec<String>,
+    /// Extensions of files to not download (ex: `exe`)
+    #[serde(default)]
+    pub unwanted_extensions: Vec<String>,
+    /// Raise the priority of the main video file
+    #[serde(default)]
+    pub prioritize_main_video: bool,
+    /// Enable sequential download for media torrents
+    #[serde(default)]
+    pub sequential_media: bool,
+}
+
+/// Compiled rules applied to files of newly added torrents
+#[derive(Debug)]
+pub struct FileRules {
+    unwanted: Vec<Pattern>,
+    unwanted_extensions: Vec<String>,
+    prioritize_main_video: bool,
+    sequential_media: bool,
+}
+
+impl FileRules {
+    /// Compile the file rules from settings
+    pub fn new(settings: &FileRulesSettings) -> Result<FileRules, PatternError> {
+        Ok(FileRules {
+            unwanted: settings
+                .unwanted
+                .iter()
+                .map(|p| Pattern::new(p))
+                .collect::<Result<Vec<_>, _>>()?,
+            unwanted_extensions: settings
+                .unwanted_extensions
+                .iter()
+                .map(|e| e.trim_start_matches('.').to_lowercase())
+                .collect(),
+            prioritize_main_video: settings.prioritize_main_video,
+            sequential_media: settings.sequential_media,
+        })
+    }
+
+    /// Method to know if a file path is unwanted
+    /// return `true` if it's the case
+    pub fn is_unwanted(&self, path: &str) -> bool {
+        if extension(path).is_some_and(|ext| self.unwanted_extensions.contains(&ext)) {
+            return true;
+        }
+
+        // Match the pattern with the path and all its sub paths
+        let mut sub_path = Some(path);
+        while let Some(path) = sub_path {
+            if self
+                .unwanted
+                .iter()
+                .any(|p| p.matches_with(path, MATCH_OPTIONS))
+            {
+                return true;
+            }
+
+            sub_path = path.split_once('/').map(|(_, sub)| sub);
+        }
+
+        false
+    }
+
+    /// Build the torrent mutation to apply the rules on a torrent content.
+    /// The torrent ids must be set by the caller.
+    /// return `None` if there is nothing to change
+    pub fn apply(
+        &self,
+        content: &TorrentContent,
+        primary_mime_type: Option<&str>,
+    ) -> Option<TorrentSetParams> {
+        let files_unwanted: Vec<usize> = content
+            .files
+            .iter()
+            .filter(|f| f.wanted && self.is_unwanted(&f.path))
+            .map(|f| f.index)
+            .collect();
+
+        let main_video = content
+            .files
+            .iter()
+            .filter(|f| !files_unwanted.contains(&f.index))
+            .filter(|f| extension(&f.path).is_some_and(|e| VIDEO_EXTENSIONS.contains(&e.as_str())))
+            .max_by_key(|f| f.size);
+        let is_media = main_video.is_some()
+            || primary_mime_type
+                .is_some_and(|m| m.starts_with("video/") || m.starts_with("audio/"));
+
+        let mut params = TorrentSetParams::default();
+        if !files_unwanted.is_empty() {
+            params.files_unwanted = Some(files_unwanted);
+        }
+
+        if self.prioritize_main_video
+            && let Some(main_video) = main_video
+        {
+            params.priority_high = Some(vec![main_video.index as u32]);
+        }
+
+        if self.sequential_media && is_media {
+            params.sequential_download = Some(true);
+        }
+
+        if params.files_unwanted.is_some()
+            || params.priority_high.is_some()
+            || params.sequential_download.is_some()
+        {
+            Some(params)
+        } else {
+            None
+        }
+    }
+}
+
+/// Getter of the lowercase extension of a file path
+fn extension(path: &str) -> Option<String> {
+    path.rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transmission::api::TorrentsArguments;
+
+    #[test]
+    fn file_rules() {
+        let rules = FileRules::new(&FileRulesSettings {
+            unwanted: vec!["*.nfo".to_string(), "Sample/*".to_string()],
+            unwanted_extensions: vec![".exe".to_string()],
+            prioritize_main_video: true,
+            sequential_media: true,
+        })
+        .unwrap();
+
+        assert!(rules.is_unwanted("Show/Show.NFO"));
+        assert!(rules.is_unwanted("Show/Sample/sample.mkv"));
+        assert!(rules.is_unwanted("Show/setup.exe"));
+        assert!(!rules.is_unwanted("Show/Show.S01E01.mkv"));
+        assert!(!rules.is_unwanted("Show/Samples.txt"));
+
+        let torrent: TorrentsArguments = serde_json::from_str(
+            r#"{
+                "files": [
+                    { "bytesCompleted": 0, "length": 1000, "name": "Show/Show.nfo", "begin_piece": 0, "end_piece": 1 },
+                    { "bytesCompleted": 0, "length": 40000, "name": "Show/Show.S01E01.mkv", "begin_piece": 0, "end_piece": 3 },
+                    { "bytesCompleted": 0, "length": 20000, "name": "Show/Sample/sample.mkv", "begin_piece": 3, "end_piece": 5 }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let content = TorrentContent::from_torrent(&torrent).unwrap();
+        let params = rules.apply(&content, None).unwrap();
+        assert_eq!(Some(vec![0, 2]), params.files_unwanted);
+        assert_eq!(Some(vec![1]), params.priority_high);
+        assert_eq!(Some(true), params.sequential_download);
+    }
+}
@@ -0,0 +1,819 @@
+use std::{borrow::Cow, time::Duration};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as base64};
+use bytes::{Buf as _, Bytes};
+use http::{Request, StatusCode};
+use http_body_util::{BodyExt as _, Full};
+use hyper::client::conn::http1;
+use hyper_util::rt::TokioIo;
+use prosa::{
+    core::{
+        adaptor::Adaptor,
+        error::{BusError, ProcError},
+        msg::{InternalMsg, Msg, Tvf},
+        proc::{Proc, ProcBusParam, proc, proc_settings},
+        service::ServiceError,
+    },
+    io::stream::TargetSetting,
+};
+use serde::{Deserialize, Serialize};
+use tokio::time;
+use tracing::{Span, debug, info, warn};
+
+use crate::transmission::{
+    api::{
+        Id, Method, Response, RpcDialect, TorrentAddParams, TorrentAdded, TorrentField, TorrentId,
+        TorrentSetParams,
+    },
+    content::TorrentContent,
+    tvf::ToTvf as _,
+};
+
+/// TVF field id of the operation (`add`, `start`, `stop`, `remove`, `set`, `get`, `stream`)
+pub const SERVICE_ACTION: usize = 1;
+/// TVF field id of the torrent ids list (ids or hashes)
+pub const SERVICE_IDS: usize = 2;
+/// TVF field id of the filename, URL or magnet link to add
+pub const SERVICE_FILENAME: usize = 3;
+/// TVF field id of the .torrent content to add
+pub const SERVICE_METAINFO: usize = 4;
+/// TVF field id of the download folder of the torrent to add
+pub const SERVICE_DOWNLOAD_DIR: usize = 5;
+/// TVF field id of the labels list to set
+pub const SERVICE_LABELS: usize = 6;
+/// TVF field id of the paused flag of the torrent to add
+pub const SERVICE_PAUSED: usize = 7;
+/// TVF field id of the flag to delete local data on remove
+pub const SERVICE_DELETE_LOCAL_DATA: usize = 8;
+/// TVF field id of the list of fields to get
+pub const SERVICE_FIELDS: usize = 9;
+/// TVF field id of the new location of the torrent content
+pub const SERVICE_LOCATION: usize = 10;
+/// TVF field id of the download limit in kB/s
+pub const SERVICE_DOWNLOAD_LIMIT: usize = 11;
+/// TVF field id of the upload limit in kB/s
+pub const SERVICE_UPLOAD_LIMIT: usize = 12;
+/// TVF field id of the index of the file to stream
+pub const SERVICE_FILE_INDEX: usize = 13;
+/// TVF field id of the byte offset in the file to stream from
+pub const SERVICE_OFFSET: usize = 14;
+
+/// TVF field id of the result of the operation (`success` or an error string)
+pub const SERVICE_RESULT: usize = 1;
+/// TVF field id of the torrents list returned by `get`
+pub const SERVICE_TORRENTS: usize = 2;
+/// TVF field id of the torrent added by `add`
+pub const SERVICE_ADDED: usize = 3;
+/// TVF field id of the flag indicating the added torrent was already there
+pub const SERVICE_DUPLICATE: usize = 4;
+
+/// Fields returned by `get` when no field is requested
+const DEFAULT_FIELDS: [TorrentField; 5] = [
+    TorrentField::Id,
+    TorrentField::HashString,
+    TorrentField::Name,
+    TorrentField::Status,
+    TorrentField::PercentDone,
+];
+
+/// Getter of the values of a TVF list, ordered by their field id
+fn get_list<M, T>(tvf: &M, id: usize, get: impl Fn(&M, usize) -> Option<T>) -> Option<Vec<T>>
+where
+    M: Tvf + Clone,
+{
+    let list = tvf.get_buffer(id).ok()?;
+    let mut keys = list.keys();
+    keys.sort_unstable();
+    Some(keys.into_iter().filter_map(|k| get(&list, k)).collect())
+}
+
+/// Build a TVF list from values
+fn put_list<M, T>(
+    tvf: &mut M,
+    id: usize,
+    values: impl Iterator<Item = T>,
+    put: impl Fn(&mut M, usize, T),
+) where
+    M: Tvf + Default,
+{
+    let mut list = M::default();
+    for (k, value) in values.enumerate() {
+        put(&mut list, k + 1, value);
+    }
+    tvf.put_buffer(id, list);
+}
+
+/// Getter of the torrent ids of a request
+fn get_ids<M>(tvf: &M) -> Option<Vec<Id>>
+where
+    M: Tvf + Clone,
+{
+    get_list(tvf, SERVICE_IDS, |list, k| {
+        list.get_signed(k)
+            .map(Id::Id)
+            .or_else(|_| list.get_string(k).map(|h| Id::Hash(h.into_owned())))
+            .ok()
+    })
+}
+
+/// Getter of a boolean flag of a request
+fn get_flag<M>(tvf: &M, id: usize) -> Option<bool>
+where
+    M: Tvf,
+{
+    tvf.get_byte(id).ok().map(|b| b != 0)
+}
+
+/// Decode a Transmission method from a service request
+pub fn method_from_tvf<M>(tvf: &M) -> Result<Method, ServiceError>
+where
+    M: Tvf + Clone,
+{
+    let action = tvf.get_string(SERVICE_ACTION)?;
+    let ids = get_ids(tvf);
+    match action.as_str() {
+        "add" => {
+            let params = TorrentAddParams {
+                download_dir: tvf
+                    .get_string(SERVICE_DOWNLOAD_DIR)
+                    .ok()
+                    .map(Cow::into_owned),
+                filename: tvf.get_string(SERVICE_FILENAME).ok().map(Cow::into_owned),
+                labels: get_list(tvf, SERVICE_LABELS, |l, k| {
+                    l.get_string(k).ok().map(Cow::into_owned)
+                }),
+                metainfo: tvf
+                    .get_bytes(SERVICE_METAINFO)
+                    .ok()
+                    .map(|m| base64.encode(m.as_ref())),
+                paused: get_flag(tvf, SERVICE_PAUSED),
+            };
+            if params.filename.is_none() && params.metainfo.is_none() {
+                return Err(ServiceError::ProtocolError(
+                    "`add` need a filename or a metainfo".to_string(),
+                ));
+            }
+            Ok(Method::TorrentAdd(Box::new(params)))
+        }
+        // Without ids, Transmission would start or stop every torrent
+        "start" => Ok(Method::TorrentStart(Some(TorrentId::List(
+            ids.ok_or_else(|| ServiceError::ProtocolError("`start` need ids".to_string()))?,
+        )))),
+        "stop" => Ok(Method::TorrentStop(Some(TorrentId::List(ids.ok_or_else(
+            || ServiceError::ProtocolError("`stop` need ids".to_string()),
+        )?)))),
+        "remove" => Ok(Method::TorrentRemove(
+            ids.ok_or_else(|| ServiceError::ProtocolError("`remove` need ids".to_string()))?
+                .into_iter()
+                .map(TorrentId::Id)
+                .collect(),
+            get_flag(tvf, SERVICE_DELETE_LOCAL_DATA).unwrap_or_default(),
+        )),
+        "set" => Ok(Method::TorrentSet(Box::new(TorrentSetParams {
+            ids: Some(
+                ids.ok_or_else(|| ServiceError::ProtocolError("`set` need ids".to_string()))?
+                    .into_iter()
+                    .map(TorrentId::Id)
+                    .collect(),
+            ),
+            labels: get_list(tvf, SERVICE_LABELS, |l, k| {
+                l.get_string(k).ok().map(Cow::into_owned)
+            }),
+            location: tvf.get_string(SERVICE_LOCATION).ok().map(Cow::into_owned),
+            download_limit: tvf.get_unsigned(SERVICE_DOWNLOAD_LIMIT).ok(),
+            download_limited: tvf.get_unsigned(SERVICE_DOWNLOAD_LIMIT).ok().map(|_| true),
+            upload_limit: tvf.get_unsigned(SERVICE_UPLOAD_LIMIT).ok(),
+            upload_limited: tvf.get_unsigned(SERVICE_UPLOAD_LIMIT).ok().map(|_| true),
+            ..Default::default()
+        }))),
+        "get" => {
+            let fields = get_list(tvf, SERVICE_FIELDS, |l, k| {
+                l.get_string(k).ok().and_then(|f| {
+                    serde_json::from_value(serde_json::Value::String(f.into_owned())).ok()
+                })
+            })
+            .filter(|f| !f.is_empty())
+            .unwrap_or_else(|| DEFAULT_FIELDS.to_vec());
+            Ok(Method::TorrentGet(
+                fields,
+                ids.map(|ids| ids.into_iter().map(TorrentId::Id).collect()),
+                None,
+            ))
+        }
+        // Get the torrent content first, the mutation is built from it by `next_method_from_tvf`
+        "stream" => {
+            let id = ids
+                .and_then(|ids| ids.into_iter().next())
+                .ok_or_else(|| ServiceError::ProtocolError("`stream` need an id".to_string()))?;
+            Ok(Method::TorrentGet(
+                TorrentContent::FIELDS.to_vec(),
+                Some(vec![TorrentId::Id(id)]),
+                None,
+            ))
+        }
+        action => Err(ServiceError::ProtocolError(format!(
+            "Unknown transmission action `{action}`"
+        ))),
+    }
+}
+
+/// Decode the Transmission method to call after the response of the first one, if the service request need several calls.
+/// `stream` enable the sequential download of the file from its offset, once the torrent content is known
+pub fn next_method_from_tvf<M>(
+    request: &M,
+    method: &Method,
+    response: &Response,
+) -> Result<Option<Method>, ServiceError>
+where
+    M: Tvf + Clone,
+{
+    // Only the content returned by the first `torrent-get` lead to a mutation
+    if request.get_string(SERVICE_ACTION)?.as_str() != "stream"
+        || !matches!(method, Method::TorrentGet(..))
+        || !response.is_success()
+    {
+        return Ok(None);
+    }
+
+    let torrent =
+        response.arguments.torrents.first().ok_or_else(|| {
+            ServiceError::ProtocolError("`stream` torrent doesn't exist".to_string())
+        })?;
+    let id = get_ids(request)
+        .and_then(|ids| ids.into_iter().next())
+        .ok_or_else(|| ServiceError::ProtocolError("`stream` need an id".to_string()))?;
+    let index = request
+        .get_unsigned(SERVICE_FILE_INDEX)
+        .map_err(|_| ServiceError::ProtocolError("`stream` need a file index".to_string()))?
+        as usize;
+    let offset = request.get_unsigned(SERVICE_OFFSET).unwrap_or_default();
+    TorrentContent::from_torrent(torrent)
+        .and_then(|content| content.stream_from(TorrentId::Id(id), index, offset))
+        .map(|params| Some(Method::TorrentSet(Box::new(params))))
+        .ok_or_else(|| {
+            ServiceError::ProtocolError(format!(
+                "`stream` file {index} or offset {offset} doesn't exist"
+            ))
+        })
+}
+
+/// Encode an added torrent into a TVF message, with the field ids of a torrent
+fn added_to_tvf<M>(added: &TorrentAdded) -> M
+where
+    M: Tvf + Default,
+{
+    let mut tvf = M::default();
+    if let Some(TorrentId::Id(Id::Id(id))) = &added.id {
+        tvf.put_signed(TorrentField::Id.tvf_id(), *id);
+    }
+    if let Some(hash) = &added.hash_string {
+        tvf.put_string(TorrentField::HashString.tvf_id(), hash.clone());
+    }
+    if let Some(name) = &added.name {
+        tvf.put_string(TorrentField::Name.tvf_id(), name.clone());
+    }
+    tvf
+}
+
+/// Encode a Transmission response into a service response
+pub fn response_to_tvf<M>(response: &Response) -> M
+where
+    M: Tvf + Default,
+{
+    let mut tvf = M::default();
+    if let Some(error) = &response.error {
+        tvf.put_string(SERVICE_RESULT, error.to_string());
+    } else {
+        tvf.put_string(SERVICE_RESULT, response.result.clone());
+    }
+    if !response.arguments.torrents.is_empty() {
+        put_list(
+            &mut tvf,
+            SERVICE_TORRENTS,
+            response.arguments.torrents.iter(),
+            |l, k, torrent| l.put_buffer(k, torrent.to_tvf()),
+        );
+    }
+    if let Some(added) = &response.arguments.torrent_added {
+        tvf.put_buffer(SERVICE_ADDED, added_to_tvf(added));
+    } else if let Some(duplicate) = &response.arguments.torrent_duplicate {
+        tvf.put_buffer(SERVICE_ADDED, added_to_tvf(duplicate));
+        tvf.put_byte(SERVICE_DUPLICATE, 1);
+    }
+    tvf
+}
+
+/// Settings of the Transmission service processor
+#[proc_settings]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TransmissionServiceSettings {
+    /// Target settings to connect to Transmission
+    target: Option<TargetSetting>,
+    /// Name of the service to register
+    #[serde(default = "TransmissionServiceSettings::get_default_service_name")]
+    pub service_name: String,
+    /// Path of the Transmission RPC
+    #[serde(default = "TransmissionServiceSettings::get_default_rpc_path")]
+    pub rpc_path: String,
+    /// Force the dialect of the RPC. Negotiated with Transmission if not set
+    pub rpc_dialect: Option<RpcDialect>,
+    /// Timeout of a Transmission call
+    #[serde(default = "TransmissionServiceSettings::get_default_timeout")]
+    pub timeout: Duration,
+}
+
+impl TransmissionServiceSettings {
+    fn get_default_service_name() -> String {
+        "TRANSMISSION".to_string()
+    }
+
+    fn get_default_rpc_path() -> String {
+        "/transmission/rpc".to_string()
+    }
+
+    fn get_default_timeout() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    /// Create a new Transmission service settings
+    pub fn new(target: TargetSetting, service_name: String) -> TransmissionServiceSettings {
+        TransmissionServiceSettings {
+            target: Some(target),
+            service_name,
+            ..Default::default()
+        }
+    }
+}
+
+#[proc_settings]
+impl Default for TransmissionServiceSettings {
+    fn default() -> Self {
+        TransmissionServiceSettings {
+            target: None,
+            service_name: Self::get_default_service_name(),
+            rpc_path: Self::get_default_rpc_path(),
+            rpc_dialect: None,
+            timeout: Self::get_default_timeout(),
+        }
+    }
+}
+
+/// Adaptor trait of the Transmission service processor, to convert service requests into Transmission methods
+pub trait TransmissionServiceAdaptor<M>
+where
+    M: 'static
+        + std::marker::Send
+        + std::marker::Sync
+        + std::marker::Sized
+        + std::clone::Clone
+        + std::fmt::Debug
+        + Tvf
+        + std::default::Default,
+{
+    /// Method called when the processor spawns
+    fn new(proc: &TransmissionServiceProc<M>) -> Result<Self, Box<dyn ProcError + Send + Sync>>
+    where
+        Self: std::marker::Sized;
+
+    /// Method to convert a service request into a Transmission method
+    fn process_request(&self, service_name: &str, request: &M) -> Result<Method, ServiceError>;
+
+    /// Method to get the next Transmission method to call after a response, if the service request need several calls.
+    /// The service response is built from the response of the last call
+    fn next_method(
+        &self,
+        _request: &M,
+        _method: &Method,
+        _response: &Response,
+    ) -> Result<Option<Method>, ServiceError> {
+        Ok(None)
+    }
+
+    /// Method to convert the Transmission response into the service response
+    fn process_response(&self, method: &Method, response: &Response) -> Result<M, ServiceError>;
+}
+
+/// Adaptor that decode requests and encode responses with the TVF field ids of this module
+#[derive(Adaptor)]
+pub struct TorrentServiceAdaptor {}
+
+impl<M> TransmissionServiceAdaptor<M> for TorrentServiceAdaptor
+where
+    M: 'static
+        + std::marker::Send
+        + std::marker::Sync
+        + std::marker::Sized
+        + std::clone::Clone
+        + std::fmt::Debug
+        + Tvf
+        + std::default::Default,
+{
+    fn new(_proc: &TransmissionServiceProc<M>) -> Result<Self, Box<dyn ProcError + Send + Sync>> {
+        Ok(TorrentServiceAdaptor {})
+    }
+
+    fn process_request(&self, _service_name: &str, request: &M) -> Result<Method, ServiceError> {
+        method_from_tvf(request)
+    }
+
+    fn next_method(
+        &self,
+        request: &M,
+        method: &Method,
+        response: &Response,
+    ) -> Result<Option<Method>, ServiceError> {
+        next_method_from_tvf(request, method, response)
+    }
+
+    fn process_response(&self, _method: &Method, response: &Response) -> Result<M, ServiceError> {
+        Ok(response_to_tvf(response))
+    }
+}
+
+/// HTTP client of the Transmission RPC, that keep the session id and the dialect between calls
+struct TransmissionClient {
+    target: TargetSetting,
+    transmission_uri: hyper::Uri,
+    session_id: Option<String>,
+    forced_dialect: Option<RpcDialect>,
+    rpc_dialect: Option<RpcDialect>,
+    request_id: u64,
+}
+
+impl TransmissionClient {
+    fn new(settings: &TransmissionServiceSettings) -> Result<Self, String> {
+        Ok(TransmissionClient {
+            target: settings
+                .target
+                .clone()
+                .ok_or_else(|| "No target to reach Transmission".to_string())?,
+            transmission_uri: settings
+                .rpc_path
+                .parse::<hyper::Uri>()
+                .map_err(|e| format!("can't parse hyper::uri `{}` : {e}", settings.rpc_path))?,
+            session_id: None,
+            forced_dialect: settings.rpc_dialect,
+            rpc_dialect: settings.rpc_dialect,
+            request_id: 1,
+        })
+    }
+
+    /// Send a method to Transmission on an HTTP connection.
+    /// The session id is renewed, and the dialect negotiated when needed
+    async fn send(
+        &mut self,
+        sender: &mut http1::SendRequest<Full<Bytes>>,
+        method: &Method,
+    ) -> Result<Response, String> {
+        loop {
+            if self.rpc_dialect.is_none() && !matches!(method, Method::SessionGet) {
+                let response = Box::pin(self.send(sender, &Method::SessionGet)).await?;
+                let rpc_dialect = RpcDialect::from_rpc_version(
+                    response.arguments.rpc_version.unwrap_or_default(),
+                );
+                info!(
+                    "Transmission {} with RPC version {:?}, use the {rpc_dialect} dialect",
+                    response.arguments.version.unwrap_or_default(),
+                    response.arguments.rpc_version
+                );
+                self.rpc_dialect = Some(rpc_dialect);
+            }
+
+            let body = serde_json::to_vec(&crate::transmission::api::Request::new(
+                method,
+                self.rpc_dialect.unwrap_or_default(),
+                Some(self.request_id),
+            ))
+            .map_err(|e| format!("can't serialize transmission method: {e}"))?;
+            let mut request_builder = Request::post(self.transmission_uri.clone())
+                .header(hyper::header::CONTENT_TYPE, "application/json")
+                .header(hyper::header::ACCEPT, "application/json");
+            if let Some(host) = self.target.url.host_str() {
+                request_builder = request_builder.header(hyper::header::HOST, host);
+            }
+            if let Some(authorization) = self.target.get_authentication() {
+                request_builder =
+                    request_builder.header(hyper::header::AUTHORIZATION, authorization);
+            }
+            if let Some(session_id) = &self.session_id {
+                request_builder = request_builder.header("X-Transmission-Session-Id", session_id);
+            }
+            let request = request_builder
+                .body(Full::new(Bytes::from(body)))
+                .map_err(|e| format!("can't build HTTP request: {e}"))?;
+
+            let response = sender
+                .send_request(request)
+                .await
+                .map_err(|e| format!("HTTP error: {e}"))?;
+            match response.status() {
+                StatusCode::OK => {
+                    self.request_id += 1;
+                    let body = response
+                        .collect()
+                        .await
+                        .map_err(|e| format!("HTTP body error: {e}"))?
+                        .aggregate();
+                    return serde_json::from_reader(body.reader())
+                        .map_err(|e| format!("can't deserialize transmission response: {e}"));
+                }
+                StatusCode::CONFLICT => {
+                    // New session, negotiate again the dialect in case Transmission was upgraded
+                    let session_id = response
+                        .headers()
+                        .get("x-transmission-session-id")
+                        .and_then(|s| s.to_str().map(|s| s.to_string()).ok());
+                    if session_id.is_none() || session_id == self.session_id {
+                        return Err(
+                            "Can't retrieve `x-transmission-session-id` from remote".to_string()
+                        );
+                    }
+                    debug!("Transmission session ID[{:?}] expired", self.session_id);
+                    self.session_id = session_id;
+                    self.rpc_dialect = self.forced_dialect;
+                }
+                code => return Err(format!("Receive error from HTTP remote: {code}")),
+            }
+        }
+    }
+
+    /// Call Transmission with a method, on a new connection
+    async fn call(&mut self, method: &Method) -> Result<Response, String> {
+        let stream = self
+            .target
+            .connect()
+            .await
+            .map_err(|e| format!("Can't connect to {}: {e}", self.target))?;
+        let (mut sender, connection) = http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| format!("HTTP handshake error: {e}"))?;
+        let connection = tokio::spawn(connection);
+        let response = self.send(&mut sender, method).await;
+        connection.abort();
+        response
+    }
+
+    /// Process a service request: call Transmission with the methods given by the adaptor, until there is no next method.
+    /// The service response is built from the response of the last call
+    async fn process<A, M>(
+        &mut self,
+        adaptor: &A,
+        service_name: &str,
+        request: &M,
+        timeout: Duration,
+        span: &Span,
+    ) -> Result<M, ServiceError>
+    where
+        A: TransmissionServiceAdaptor<M>,
+        M: 'static
+            + std::marker::Send
+            + std::marker::Sync
+            + std::marker::Sized
+            + std::clone::Clone
+            + std::fmt::Debug
+            + Tvf
+            + std::default::Default,
+    {
+        let mut method = adaptor.process_request(service_name, request)?;
+        loop {
+            let response = match time::timeout(timeout, self.call(&method)).await {
+                Ok(Ok(response)) => response,
+                Ok(Err(e)) => {
+                    warn!(parent: span, service = service_name, "Transmission call error: {e}");
+                    return Err(ServiceError::UnableToReachService(service_name.to_string()));
+                }
+                Err(_) => {
+                    return Err(ServiceError::Timeout(
+                        service_name.to_string(),
+                        timeout.as_millis() as u64,
+                    ));
+                }
+            };
+
+            match adaptor.next_method(request, &method, &response)? {
+                Some(next_method) => method = next_method,
+                None => return adaptor.process_response(&method, &response),
+            }
+        }
+    }
+}
+
+/// Processor that register a ProSA service to drive Transmission with TVF messages
+///
+/// The service request contain the operation in [`SERVICE_ACTION`], with its parameters in the others `SERVICE_*` field ids.
+/// The response contain the result of Transmission in [`SERVICE_RESULT`], and torrents in [`SERVICE_TORRENTS`] for `get`.
+#[proc(settings = TransmissionServiceSettings)]
+pub struct TransmissionServiceProc {}
+
+#[proc]
+impl<A> Proc<A> for TransmissionServiceProc
+where
+    A: 'static + Adaptor + TransmissionServiceAdaptor<M> + std::marker::Send + std::marker::Sync,
+{
+    async fn internal_run(&mut self) -> Result<(), Box<dyn ProcError + Send + Sync>> {
+        let adaptor = A::new(self)?;
+        let mut client = TransmissionClient::new(&self.settings).map_err(std::io::Error::other)?;
+
+        // Declare the processor and its service
+        self.proc.add_proc().await?;
+        self.proc
+            .add_service_proc(vec![self.settings.service_name.clone()])
+            .await?;
+
+        loop {
+            if let Some(msg) = self.internal_rx_queue.recv().await {
+                match msg {
+                    InternalMsg::Request(mut msg) => {
+                        let request_data = msg.take_data().ok_or(BusError::NoData)?;
+                        let service_name = msg.get_service().clone();
+                        debug!(parent: msg.get_span(), service = service_name, "Transmission request {request_data:?}");
+
+                        let result = client
+                            .process(
+                                &adaptor,
+                                &service_name,
+                                &request_data,
+                                self.settings.timeout,
+                                msg.get_span(),
+                            )
+                            .await;
+                        let _ = msg.return_result_to_sender(result);
+                    }
+                    InternalMsg::Response(msg) => warn!(
+                        "The transmission service processor {} receive a response {:?}",
+                        self.get_proc_id(),
+                        msg
+                    ),
+                    InternalMsg::Error(err) => warn!(
+                        "The transmission service processor {} receive an error {:?}",
+                        self.get_proc_id(),
+                        err
+                    ),
+                    InternalMsg::Command(_) => {}
+                    InternalMsg::Config => {}
+                    InternalMsg::Service(table) => self.service = table,
+                    InternalMsg::Shutdown => {
+                        adaptor.terminate();
+                        self.proc.remove_proc(None).await?;
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prosa_utils::msg::simple_string_tvf::SimpleStringTvf;
+
+    use serde_json::json;
+
+    use crate::transmission::{api::TorrentsArguments, mock::MockTransmission, tvf::FromTvf as _};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn stream_service() {
+        let mock = MockTransmission::new(vec![
+            json!({ "id": 7, "name": "Movie", "pieceCount": 8, "pieceSize": 16384,
+            "files": [
+                { "bytesCompleted": 0, "length": 20000, "name": "Movie/Movie.nfo" },
+                { "bytesCompleted": 0, "length": 100000, "name": "Movie/Movie.mkv" }
+            ] }),
+        ]);
+        let addr = mock.serve().await.unwrap();
+        let mut client = TransmissionClient::new(&TransmissionServiceSettings {
+            target: Some(
+                serde_json::from_value(json!({ "url": format!("http://{addr}") })).unwrap(),
+            ),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut request = SimpleStringTvf::default();
+        request.put_string(SERVICE_ACTION, "stream");
+        put_list(&mut request, SERVICE_IDS, [7i64].into_iter(), |l, k, id| {
+            l.put_signed(k, id)
+        });
+        request.put_unsigned(SERVICE_FILE_INDEX, 1);
+        request.put_unsigned(SERVICE_OFFSET, 50000);
+        let response: SimpleStringTvf = client
+            .process(
+                &TorrentServiceAdaptor {},
+                "TRANSMISSION",
+                &request,
+                Duration::from_secs(5),
+                &Span::none(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            "success",
+            response.get_string(SERVICE_RESULT).unwrap().as_str()
+        );
+        assert_eq!(
+            vec!["session-get", "torrent-get", "torrent-set"],
+            mock.methods()
+        );
+        let torrent = &mock.torrents()[0];
+        assert_eq!(json!(true), torrent["sequential_download"]);
+        assert_eq!(json!(4), torrent["sequential_download_from_piece"]);
+    }
+
+    #[test]
+    fn service_tvf() {
+        let mut request = SimpleStringTvf::default();
+        request.put_string(SERVICE_ACTION, "remove");
+        put_list(
+            &mut request,
+            SERVICE_IDS,
+            [1i64, 2].into_iter(),
+            |l, k, id| l.put_signed(k, id),
+        );
+        request.put_byte(SERVICE_DELETE_LOCAL_DATA, 1);
+        let method = method_from_tvf(&request).unwrap();
+        assert_eq!(
+            r#"{"method":"torrent-remove","arguments":{"ids":[1,2],"delete-local-data":true}}"#,
+            serde_json::to_string(&method).unwrap()
+        );
+
+        let mut request = SimpleStringTvf::default();
+        request.put_string(SERVICE_ACTION, "add");
+        request.put_string(SERVICE_FILENAME, "magnet:?xt=urn:btih:abc");
+        request.put_string(SERVICE_DOWNLOAD_DIR, "/data");
+        let method = method_from_tvf(&request).unwrap();
+        assert_eq!(
+            r#"{"method":"torrent-add","arguments":{"download-dir":"/data","filename":"magnet:?xt=urn:btih:abc"}}"#,
+            serde_json::to_string(&method).unwrap()
+        );
+
+        let mut request = SimpleStringTvf::default();
+        request.put_string(SERVICE_ACTION, "get");
+        put_list(
+            &mut request,
+            SERVICE_FIELDS,
+            ["id", "name"].into_iter(),
+            |l, k, f| l.put_string(k, f),
+        );
+        let method = method_from_tvf(&request).unwrap();
+        assert_eq!(
+            r#"{"method":"torrent-get","arguments":{"fields":["id","name"]}}"#,
+            serde_json::to_string(&method).unwrap()
+        );
+
+        request.put_string(SERVICE_ACTION, "stop");
+        assert!(method_from_tvf(&request).is_err());
+        request.put_string(SERVICE_ACTION, "unknown");
+        assert!(method_from_tvf(&request).is_err());
+
+        let mut request = SimpleStringTvf::default();
+        request.put_string(SERVICE_ACTION, "stream");
+        put_list(&mut request, SERVICE_IDS, [7i64].into_iter(), |l, k, id| {
+            l.put_signed(k, id)
+        });
+        request.put_unsigned(SERVICE_FILE_INDEX, 1);
+        request.put_unsigned(SERVICE_OFFSET, 50000);
+        let method = method_from_tvf(&request).unwrap();
+        assert_eq!(
+            r#"{"method":"torrent-get","arguments":{"fields":["files","fileStats","pieces","pieceCount","pieceSize","availability","sequential_download"],"ids":[7]}}"#,
+            serde_json::to_string(&method).unwrap()
+        );
+        let response: Response = serde_json::from_str(
+            r#"{ "arguments": { "torrents": [ { "files": [
+                { "bytesCompleted": 0, "length": 20000, "name": "Movie/Movie.nfo" },
+                { "bytesCompleted": 0, "length": 100000, "name": "Movie/Movie.mkv" }
+            ], "pieceCount": 8, "pieceSize": 16384 } ] }, "result": "success" }"#,
+        )
+        .unwrap();
+        let set_method = next_method_from_tvf(&request, &method, &response)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            r#"{"method":"torrent-set","arguments":{"files-wanted":[1],"ids":[7],"priority-high":[1],"sequential_download":true,"sequential_download_from_piece":4}}"#,
+            serde_json::to_string(&set_method).unwrap()
+        );
+        assert!(
+            next_method_from_tvf(&request, &set_method, &response)
+                .unwrap()
+                .is_none()
+        );
+        let response: Response =
+            serde_json::from_str(r#"{ "arguments": { "torrents": [] }, "result": "success" }"#)
+                .unwrap();
+        assert!(next_method_from_tvf(&request, &method, &response).is_err());
+
+        let response: Response = serde_json::from_str(
+            r#"{ "arguments": { "torrents": [ { "id": 1, "name": "Movie", "status": 6, "labels": ["tv"] } ] }, "result": "success" }"#,
+        )
+        .unwrap();
+        let tvf: SimpleStringTvf = response_to_tvf(&response);
+        assert_eq!("success", tvf.get_string(SERVICE_RESULT).unwrap().as_str());
+        let torrents = tvf.get_buffer(SERVICE_TORRENTS).unwrap();
+        let torrent =
+            TorrentsArguments::from_tvf(torrents.get_buffer(1).unwrap().as_ref()).unwrap();
+        assert_eq!(Some(TorrentId::Id(Id::Id(1))), torrent.id);
+        assert_eq!(Some("Movie"), torrent.name.as_deref());
+        assert_eq!(Some(vec!["tv".to_string()]), torrent.labels);
+    }
+}
@@ -0,0 +1,1701 @@
+use bytes::Bytes;
+use chrono::{DateTime, TimeDelta, Utc};
+use prosa::core::msg::Tvf;
+use serde::de::DeserializeOwned;
+
+use crate::transmission::api::{
+    File, FileStat, Id, Method, Peer, PeerFrom, SessionStats, TorrentAddParams, TorrentField,
+    TorrentGetFormat, TorrentId, TorrentSetParams, TorrentsArguments, Tracker, TrackerStat,
+};
+
+/// Encode a type into a TVF message with stable field ids
+pub trait ToTvf {
+    /// Encode into a TVF message
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default;
+}
+
+/// Decode a type from a TVF message with stable field ids
+pub trait FromTvf: Sized {
+    /// Decode from a TVF message. Return `None` if a mandatory field is missing
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone;
+}
+
+/// Value that can be put in or get from a TVF field
+trait TvfValue: Sized {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default;
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone;
+}
+
+impl TvfValue for String {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_string(id, self.clone());
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_string(id).ok().map(|s| s.into_owned())
+    }
+}
+
+impl TvfValue for bool {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_byte(id, *self as u8);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_byte(id).ok().map(|b| b != 0)
+    }
+}
+
+impl TvfValue for u64 {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_unsigned(id, *self);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_unsigned(id).ok()
+    }
+}
+
+impl TvfValue for u32 {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_unsigned(id, *self as u64);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_unsigned(id).ok()?.try_into().ok()
+    }
+}
+
+impl TvfValue for u16 {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_unsigned(id, *self as u64);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_unsigned(id).ok()?.try_into().ok()
+    }
+}
+
+impl TvfValue for usize {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_unsigned(id, *self as u64);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_unsigned(id).ok()?.try_into().ok()
+    }
+}
+
+impl TvfValue for i64 {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_signed(id, *self);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_signed(id).ok()
+    }
+}
+
+impl TvfValue for f64 {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_float(id, *self);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_float(id).ok()
+    }
+}
+
+impl TvfValue for f32 {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_float(id, *self as f64);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_float(id).ok().map(|f| f as f32)
+    }
+}
+
+impl TvfValue for DateTime<Utc> {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_datetime(id, self.naive_utc());
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_datetime(id).ok().map(|d| d.and_utc())
+    }
+}
+
+/// Durations are encoded in seconds
+impl TvfValue for TimeDelta {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_signed(id, self.num_seconds());
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        TimeDelta::try_seconds(tvf.get_signed(id).ok()?)
+    }
+}
+
+/// Torrent ids are encoded as signed, and hashes as string
+impl TvfValue for Id {
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        match self {
+            Id::Id(torrent_id) => tvf.put_signed(id, *torrent_id),
+            Id::Hash(hash) => tvf.put_string(id, hash.clone()),
+        }
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        tvf.get_signed(id)
+            .map(Id::Id)
+            .or_else(|_| tvf.get_string(id).map(|h| Id::Hash(h.into_owned())))
+            .ok()
+    }
+}
+
+/// Lists are encoded in a buffer with field ids starting from 1
+impl<T> TvfValue for Vec<T>
+where
+    T: TvfValue,
+{
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        let mut list = M::default();
+        for (k, value) in self.iter().enumerate() {
+            value.put(&mut list, k + 1);
+        }
+        tvf.put_buffer(id, list);
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        let list = tvf.get_buffer(id).ok()?;
+        let mut keys = list.keys();
+        keys.sort_unstable();
+        keys.into_iter().map(|k| T::get(list.as_ref(), k)).collect()
+    }
+}
+
+/// Structures are encoded in a buffer
+impl<T> TvfValue for T
+where
+    T: ToTvf + FromTvf,
+{
+    fn put<M>(&self, tvf: &mut M, id: usize)
+    where
+        M: Tvf + Default,
+    {
+        tvf.put_buffer(id, self.to_tvf());
+    }
+
+    fn get<M>(tvf: &M, id: usize) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        T::from_tvf(tvf.get_buffer(id).ok()?.as_ref())
+    }
+}
+
+/// Put an optional value in a TVF field
+fn put<M, T>(tvf: &mut M, id: usize, value: &Option<T>)
+where
+    M: Tvf + Default,
+    T: TvfValue,
+{
+    if let Some(value) = value {
+        value.put(tvf, id);
+    }
+}
+
+/// Get a value from a TVF field
+fn get<M, T>(tvf: &M, id: usize) -> Option<T>
+where
+    M: Tvf + Clone,
+    T: TvfValue,
+{
+    T::get(tvf, id)
+}
+
+/// Get an enum from its numeric representation
+fn from_repr<T>(repr: i64) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_value(repr.into()).ok()
+}
+
+/// Get an enum from a TVF field that contain its numeric representation
+fn get_repr<M, T>(tvf: &M, id: usize) -> Option<T>
+where
+    M: Tvf + Clone,
+    T: DeserializeOwned,
+{
+    from_repr(tvf.get_signed(id).ok()?)
+}
+
+/// TVF field id of the deprecated `manualAnnounceTime` of a torrent, that doesn't have a torrent field
+const TORRENT_MANUAL_ANNOUNCE_TIME: usize = 79;
+
+/// TVF field id of the single torrent id (signed) or hash (string)
+pub const TORRENT_ID_ID: usize = 1;
+/// TVF field id of the list of torrent ids or hashes
+pub const TORRENT_ID_LIST: usize = 2;
+/// TVF field id of the `recently-active` flag
+pub const TORRENT_ID_RECENTLY_ACTIVE: usize = 3;
+
+/// TVF field id of the RPC method name
+pub const METHOD_NAME: usize = 1;
+/// TVF field id of the torrent ids of the method
+pub const METHOD_IDS: usize = 2;
+/// TVF field id of the fields list of `torrent-get`
+pub const METHOD_FIELDS: usize = 3;
+/// TVF field id of the table format flag of `torrent-get`
+pub const METHOD_TABLE: usize = 4;
+/// TVF field id of the flag to delete local data of `torrent-remove`
+pub const METHOD_DELETE_LOCAL_DATA: usize = 5;
+/// TVF field id of the parameters of `torrent-set` or `torrent-add`
+pub const METHOD_ARGUMENTS: usize = 6;
+
+/// TVF field id of the `bytes_completed` of a file of a torrent
+pub const FILE_BYTES_COMPLETED: usize = 1;
+/// TVF field id of the `length` of a file of a torrent
+pub const FILE_LENGTH: usize = 2;
+/// TVF field id of the `name` of a file of a torrent
+pub const FILE_NAME: usize = 3;
+/// TVF field id of the `begin_piece` of a file of a torrent
+pub const FILE_BEGIN_PIECE: usize = 4;
+/// TVF field id of the `end_piece` of a file of a torrent
+pub const FILE_END_PIECE: usize = 5;
+
+/// TVF field id of the `bytes_completed` of a file statistics of a torrent
+pub const FILE_STAT_BYTES_COMPLETED: usize = 1;
+/// TVF field id of the `wanted` of a file statistics of a torrent
+pub const FILE_STAT_WANTED: usize = 2;
+/// TVF field id of the `priority` of a file statistics of a torrent
+pub const FILE_STAT_PRIORITY: usize = 3;
+
+/// TVF field id of the `address` of a peer of a torrent
+pub const PEER_ADDRESS: usize = 1;
+/// TVF field id of the `bytes_to_client` of a peer of a torrent
+pub const PEER_BYTES_TO_CLIENT: usize = 2;
+/// TVF field id of the `bytes_to_peer` of a peer of a torrent
+pub const PEER_BYTES_TO_PEER: usize = 3;
+/// TVF field id of the `client_name` of a peer of a torrent
+pub const PEER_CLIENT_NAME: usize = 4;
+/// TVF field id of the `client_is_choked` of a peer of a torrent
+pub const PEER_CLIENT_IS_CHOKED: usize = 5;
+/// TVF field id of the `client_is_interested` of a peer of a torrent
+pub const PEER_CLIENT_IS_INTERESTED: usize = 6;
+/// TVF field id of the `flag_str` of a peer of a torrent
+pub const PEER_FLAG_STR: usize = 7;
+/// TVF field id of the `is_downloading_from` of a peer of a torrent
+pub const PEER_IS_DOWNLOADING_FROM: usize = 8;
+/// TVF field id of the `is_encrypted` of a peer of a torrent
+pub const PEER_IS_ENCRYPTED: usize = 9;
+/// TVF field id of the `is_incoming` of a peer of a torrent
+pub const PEER_IS_INCOMING: usize = 10;
+/// TVF field id of the `is_uploading_to` of a peer of a torrent
+pub const PEER_IS_UPLOADING_TO: usize = 11;
+/// TVF field id of the `is_utp` of a peer of a torrent
+pub const PEER_IS_UTP: usize = 12;
+/// TVF field id of the `peer_is_choked` of a peer of a torrent
+pub const PEER_PEER_IS_CHOKED: usize = 13;
+/// TVF field id of the `peer_is_interested` of a peer of a torrent
+pub const PEER_PEER_IS_INTERESTED: usize = 14;
+/// TVF field id of the `peer_id` of a peer of a torrent
+pub const PEER_PEER_ID: usize = 15;
+/// TVF field id of the `port` of a peer of a torrent
+pub const PEER_PORT: usize = 16;
+/// TVF field id of the `progress` of a peer of a torrent
+pub const PEER_PROGRESS: usize = 17;
+/// TVF field id of the `rate_to_client` of a peer of a torrent
+pub const PEER_RATE_TO_CLIENT: usize = 18;
+/// TVF field id of the `rate_to_peer` of a peer of a torrent
+pub const PEER_RATE_TO_PEER: usize = 19;
+
+/// TVF field id of the `from_cache` of a origin counters of the peers of a torrent
+pub const PEER_FROM_FROM_CACHE: usize = 1;
+/// TVF field id of the `from_dht` of a origin counters of the peers of a torrent
+pub const PEER_FROM_FROM_DHT: usize = 2;
+/// TVF field id of the `from_incoming` of a origin counters of the peers of a torrent
+pub const PEER_FROM_FROM_INCOMING: usize = 3;
+/// TVF field id of the `from_lpd` of a origin counters of the peers of a torrent
+pub const PEER_FROM_FROM_LPD: usize = 4;
+/// TVF field id of the `from_ltep` of a origin counters of the peers of a torrent
+pub const PEER_FROM_FROM_LTEP: usize = 5;
+/// TVF field id of the `from_pex` of a origin counters of the peers of a torrent
+pub const PEER_FROM_FROM_PEX: usize = 6;
+/// TVF field id of the `from_tracker` of a origin counters of the peers of a torrent
+pub const PEER_FROM_FROM_TRACKER: usize = 7;
+
+/// TVF field id of the `announce` of a tracker of a torrent
+pub const TRACKER_ANNOUNCE: usize = 1;
+/// TVF field id of the `id` of a tracker of a torrent
+pub const TRACKER_ID: usize = 2;
+/// TVF field id of the `scrape` of a tracker of a torrent
+pub const TRACKER_SCRAPE: usize = 3;
+/// TVF field id of the `sitename` of a tracker of a torrent
+pub const TRACKER_SITENAME: usize = 4;
+/// TVF field id of the `tier` of a tracker of a torrent
+pub const TRACKER_TIER: usize = 5;
+
+/// TVF field id of the `announce` of a tracker statistics of a torrent
+pub const TRACKER_STAT_ANNOUNCE: usize = 1;
+/// TVF field id of the `announce_state` of a tracker statistics of a torrent
+pub const TRACKER_STAT_ANNOUNCE_STATE: usize = 2;
+/// TVF field id of the `download_count` of a tracker statistics of a torrent
+pub const TRACKER_STAT_DOWNLOAD_COUNT: usize = 3;
+/// TVF field id of the `downloader_count` of a tracker statistics of a torrent
+pub const TRACKER_STAT_DOWNLOADER_COUNT: usize = 4;
+/// TVF field id of the `has_announced` of a tracker statistics of a torrent
+pub const TRACKER_STAT_HAS_ANNOUNCED: usize = 5;
+/// TVF field id of the `has_scraped` of a tracker statistics of a torrent
+pub const TRACKER_STAT_HAS_SCRAPED: usize = 6;
+/// TVF field id of the `host` of a tracker statistics of a torrent
+pub const TRACKER_STAT_HOST: usize = 7;
+/// TVF field id of the `id` of a tracker statistics of a torrent
+pub const TRACKER_STAT_ID: usize = 8;
+/// TVF field id of the `is_backup` of a tracker statistics of a torrent
+pub const TRACKER_STAT_IS_BACKUP: usize = 9;
+/// TVF field id of the `last_announce_peer_count` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_ANNOUNCE_PEER_COUNT: usize = 10;
+/// TVF field id of the `last_announce_result` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_ANNOUNCE_RESULT: usize = 11;
+/// TVF field id of the `last_announce_start_time` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_ANNOUNCE_START_TIME: usize = 12;
+/// TVF field id of the `last_announce_succeeded` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_ANNOUNCE_SUCCEEDED: usize = 13;
+/// TVF field id of the `last_announce_time` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_ANNOUNCE_TIME: usize = 14;
+/// TVF field id of the `last_announce_timed_out` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_ANNOUNCE_TIMED_OUT: usize = 15;
+/// TVF field id of the `last_scrape_result` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_SCRAPE_RESULT: usize = 16;
+/// TVF field id of the `last_scrape_start_time` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_SCRAPE_START_TIME: usize = 17;
+/// TVF field id of the `last_scrape_succeeded` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_SCRAPE_SUCCEEDED: usize = 18;
+/// TVF field id of the `last_scrape_time` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_SCRAPE_TIME: usize = 19;
+/// TVF field id of the `last_scrape_timed_out` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LAST_SCRAPE_TIMED_OUT: usize = 20;
+/// TVF field id of the `leecher_count` of a tracker statistics of a torrent
+pub const TRACKER_STAT_LEECHER_COUNT: usize = 21;
+/// TVF field id of the `next_announce_time` of a tracker statistics of a torrent
+pub const TRACKER_STAT_NEXT_ANNOUNCE_TIME: usize = 22;
+/// TVF field id of the `next_scrape_time` of a tracker statistics of a torrent
+pub const TRACKER_STAT_NEXT_SCRAPE_TIME: usize = 23;
+/// TVF field id of the `scrape` of a tracker statistics of a torrent
+pub const TRACKER_STAT_SCRAPE: usize = 24;
+/// TVF field id of the `scrape_state` of a tracker statistics of a torrent
+pub const TRACKER_STAT_SCRAPE_STATE: usize = 25;
+/// TVF field id of the `seeder_count` of a tracker statistics of a torrent
+pub const TRACKER_STAT_SEEDER_COUNT: usize = 26;
+/// TVF field id of the `sitename` of a tracker statistics of a torrent
+pub const TRACKER_STAT_SITENAME: usize = 27;
+/// TVF field id of the `tier` of a tracker statistics of a torrent
+pub const TRACKER_STAT_TIER: usize = 28;
+
+/// TVF field id of the `uploaded_bytes` of a session statistics
+pub const SESSION_STATS_UPLOADED_BYTES: usize = 1;
+/// TVF field id of the `downloaded_bytes` of a session statistics
+pub const SESSION_STATS_DOWNLOADED_BYTES: usize = 2;
+/// TVF field id of the `files_added` of a session statistics
+pub const SESSION_STATS_FILES_ADDED: usize = 3;
+/// TVF field id of the `seconds_active` of a session statistics
+pub const SESSION_STATS_SECONDS_ACTIVE: usize = 4;
+/// TVF field id of the `session_count` of a session statistics
+pub const SESSION_STATS_SESSION_COUNT: usize = 5;
+
+/// TVF field id of the `bandwidth_priority` of the `torrent-set` parameters
+pub const TORRENT_SET_BANDWIDTH_PRIORITY: usize = 1;
+/// TVF field id of the `download_limit` of the `torrent-set` parameters
+pub const TORRENT_SET_DOWNLOAD_LIMIT: usize = 2;
+/// TVF field id of the `download_limited` of the `torrent-set` parameters
+pub const TORRENT_SET_DOWNLOAD_LIMITED: usize = 3;
+/// TVF field id of the `files_unwanted` of the `torrent-set` parameters
+pub const TORRENT_SET_FILES_UNWANTED: usize = 4;
+/// TVF field id of the `files_wanted` of the `torrent-set` parameters
+pub const TORRENT_SET_FILES_WANTED: usize = 5;
+/// TVF field id of the `group` of the `torrent-set` parameters
+pub const TORRENT_SET_GROUP: usize = 6;
+/// TVF field id of the `honors_session_limits` of the `torrent-set` parameters
+pub const TORRENT_SET_HONORS_SESSION_LIMITS: usize = 7;
+/// TVF field id of the `ids` of the `torrent-set` parameters
+pub const TORRENT_SET_IDS: usize = 8;
+/// TVF field id of the `labels` of the `torrent-set` parameters
+pub const TORRENT_SET_LABELS: usize = 9;
+/// TVF field id of the `location` of the `torrent-set` parameters
+pub const TORRENT_SET_LOCATION: usize = 10;
+/// TVF field id of the `peer_limit` of the `torrent-set` parameters
+pub const TORRENT_SET_PEER_LIMIT: usize = 11;
+/// TVF field id of the `priority_high` of the `torrent-set` parameters
+pub const TORRENT_SET_PRIORITY_HIGH: usize = 12;
+/// TVF field id of the `priority_low` of the `torrent-set` parameters
+pub const TORRENT_SET_PRIORITY_LOW: usize = 13;
+/// TVF field id of the `priority_normal` of the `torrent-set` parameters
+pub const TORRENT_SET_PRIORITY_NORMAL: usize = 14;
+/// TVF field id of the `queue_position` of the `torrent-set` parameters
+pub const TORRENT_SET_QUEUE_POSITION: usize = 15;
+/// TVF field id of the `seed_idle_limit` of the `torrent-set` parameters
+pub const TORRENT_SET_SEED_IDLE_LIMIT: usize = 16;
+/// TVF field id of the `seed_idle_mode` of the `torrent-set` parameters
+pub const TORRENT_SET_SEED_IDLE_MODE: usize = 17;
+/// TVF field id of the `seed_ratio_limit` of the `torrent-set` parameters
+pub const TORRENT_SET_SEED_RATIO_LIMIT: usize = 18;
+/// TVF field id of the `seed_ratio_mode` of the `torrent-set` parameters
+pub const TORRENT_SET_SEED_RATIO_MODE: usize = 19;
+/// TVF field id of the `sequential_download` of the `torrent-set` parameters
+pub const TORRENT_SET_SEQUENTIAL_DOWNLOAD: usize = 20;
+/// TVF field id of the `sequential_download_from_piece` of the `torrent-set` parameters
+pub const TORRENT_SET_SEQUENTIAL_DOWNLOAD_FROM_PIECE: usize = 21;
+/// TVF field id of the `tracker_add` of the `torrent-set` parameters
+pub const TORRENT_SET_TRACKER_ADD: usize = 22;
+/// TVF field id of the `tracker_list` of the `torrent-set` parameters
+pub const TORRENT_SET_TRACKER_LIST: usize = 23;
+/// TVF field id of the `tracker_remove` of the `torrent-set` parameters
+pub const TORRENT_SET_TRACKER_REMOVE: usize = 24;
+/// TVF field id of the `tracker_replace` of the `torrent-set` parameters
+pub const TORRENT_SET_TRACKER_REPLACE: usize = 25;
+/// TVF field id of the `upload_limit` of the `torrent-set` parameters
+pub const TORRENT_SET_UPLOAD_LIMIT: usize = 26;
+/// TVF field id of the `upload_limited` of the `torrent-set` parameters
+pub const TORRENT_SET_UPLOAD_LIMITED: usize = 27;
+
+/// TVF field id of the `download_dir` of the `torrent-add` parameters
+pub const TORRENT_ADD_DOWNLOAD_DIR: usize = 1;
+/// TVF field id of the `filename` of the `torrent-add` parameters
+pub const TORRENT_ADD_FILENAME: usize = 2;
+/// TVF field id of the `labels` of the `torrent-add` parameters
+pub const TORRENT_ADD_LABELS: usize = 3;
+/// TVF field id of the `metainfo` of the `torrent-add` parameters
+pub const TORRENT_ADD_METAINFO: usize = 4;
+/// TVF field id of the `paused` of the `torrent-add` parameters
+pub const TORRENT_ADD_PAUSED: usize = 5;
+impl TorrentField {
+    /// Stable TVF field id of the torrent field
+    pub fn tvf_id(&self) -> usize {
+        match self {
+            TorrentField::ActivityDate => 1,
+            TorrentField::AddedDate => 2,
+            TorrentField::Availability => 3,
+            TorrentField::BandwidthPriority => 4,
+            TorrentField::BytesCompleted => 5,
+            TorrentField::Comment => 6,
+            TorrentField::CorruptEver => 7,
+            TorrentField::Creator => 8,
+            TorrentField::DateCreated => 9,
+            TorrentField::DesiredAvailable => 10,
+            TorrentField::DoneDate => 11,
+            TorrentField::DownloadDir => 12,
+            TorrentField::DownloadedEver => 13,
+            TorrentField::DownloadLimit => 14,
+            TorrentField::DownloadLimited => 15,
+            TorrentField::EditDate => 16,
+            TorrentField::Error => 17,
+            TorrentField::ErrorString => 18,
+            TorrentField::Eta => 19,
+            TorrentField::EtaIdle => 20,
+            TorrentField::FileCount => 21,
+            TorrentField::Files => 22,
+            TorrentField::FileStats => 23,
+            TorrentField::Group => 24,
+            TorrentField::HashString => 25,
+            TorrentField::HaveUnchecked => 26,
+            TorrentField::HaveValid => 27,
+            TorrentField::HonorsSessionLimits => 28,
+            TorrentField::Id => 29,
+            TorrentField::IsFinished => 30,
+            TorrentField::IsPrivate => 31,
+            TorrentField::IsStalled => 32,
+            TorrentField::Labels => 33,
+            TorrentField::LeftUntilDone => 34,
+            TorrentField::MagnetLink => 35,
+            TorrentField::MaxConnectedPeers => 36,
+            TorrentField::MetadataPercentComplete => 37,
+            TorrentField::Name => 38,
+            TorrentField::PeerLimit => 39,
+            TorrentField::Peers => 40,
+            TorrentField::PeersConnected => 41,
+            TorrentField::PeersFrom => 42,
+            TorrentField::PeersGettingFromUs => 43,
+            TorrentField::PeersSendingToUs => 44,
+            TorrentField::PercentComplete => 45,
+            TorrentField::PercentDone => 46,
+            TorrentField::Pieces => 47,
+            TorrentField::PieceCount => 48,
+            TorrentField::PieceSize => 49,
+            TorrentField::Priorities => 50,
+            TorrentField::PrimaryMimeType => 51,
+            TorrentField::QueuePosition => 52,
+            TorrentField::RateDownload => 53,
+            TorrentField::RateUpload => 54,
+            TorrentField::RecheckProgress => 55,
+            TorrentField::SecondsDownloading => 56,
+            TorrentField::SecondsSeeding => 57,
+            TorrentField::SeedIdleLimit => 58,
+            TorrentField::SeedIdleMode => 59,
+            TorrentField::SeedRatioLimit => 60,
+            TorrentField::SeedRatioMode => 61,
+            TorrentField::SequentialDownload => 62,
+            TorrentField::SequentialDownloadFromPiece => 63,
+            TorrentField::SizeWhenDone => 64,
+            TorrentField::StartDate => 65,
+            TorrentField::Status => 66,
+            TorrentField::Trackers => 67,
+            TorrentField::TrackerList => 68,
+            TorrentField::TrackerStats => 69,
+            TorrentField::TotalSize => 70,
+            TorrentField::TorrentFile => 71,
+            TorrentField::UploadedEver => 72,
+            TorrentField::UploadLimit => 73,
+            TorrentField::UploadLimited => 74,
+            TorrentField::UploadRatio => 75,
+            TorrentField::Wanted => 76,
+            TorrentField::Webseeds => 77,
+            TorrentField::WebseedsSendingToUs => 78,
+        }
+    }
+}
+
+impl ToTvf for TorrentsArguments {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        put(
+            &mut tvf,
+            TorrentField::ActivityDate.tvf_id(),
+            &self.activity_date,
+        );
+        put(&mut tvf, TorrentField::AddedDate.tvf_id(), &self.added_date);
+        put(
+            &mut tvf,
+            TorrentField::Availability.tvf_id(),
+            &self.availability,
+        );
+        put(
+            &mut tvf,
+            TorrentField::BandwidthPriority.tvf_id(),
+            &self.bandwidth_priority.map(|e| e as i64),
+        );
+        put(
+            &mut tvf,
+            TorrentField::BytesCompleted.tvf_id(),
+            &self.bytes_completed,
+        );
+        put(&mut tvf, TorrentField::Comment.tvf_id(), &self.comment);
+        put(
+            &mut tvf,
+            TorrentField::CorruptEver.tvf_id(),
+            &self.corrupt_ever,
+        );
+        put(&mut tvf, TorrentField::Creator.tvf_id(), &self.creator);
+        put(
+            &mut tvf,
+            TorrentField::DateCreated.tvf_id(),
+            &self.date_created,
+        );
+        put(
+            &mut tvf,
+            TorrentField::DesiredAvailable.tvf_id(),
+            &self.desired_available,
+        );
+        put(&mut tvf, TorrentField::DoneDate.tvf_id(), &self.done_date);
+        put(
+            &mut tvf,
+            TorrentField::DownloadDir.tvf_id(),
+            &self.download_dir,
+        );
+        put(
+            &mut tvf,
+            TorrentField::DownloadedEver.tvf_id(),
+            &self.downloaded_ever,
+        );
+        put(
+            &mut tvf,
+            TorrentField::DownloadLimit.tvf_id(),
+            &self.download_limit,
+        );
+        self.download_limited
+            .put(&mut tvf, TorrentField::DownloadLimited.tvf_id());
+        put(&mut tvf, TorrentField::EditDate.tvf_id(), &self.edit_date);
+        put(
+            &mut tvf,
+            TorrentField::Error.tvf_id(),
+            &self.error.map(|e| e as i64),
+        );
+        put(
+            &mut tvf,
+            TorrentField::ErrorString.tvf_id(),
+            &self.error_string,
+        );
+        put(&mut tvf, TorrentField::Eta.tvf_id(), &self.eta);
+        put(&mut tvf, TorrentField::EtaIdle.tvf_id(), &self.eta_idle);
+        put(&mut tvf, TorrentField::FileCount.tvf_id(), &self.file_count);
+        put(&mut tvf, TorrentField::Files.tvf_id(), &self.files);
+        put(&mut tvf, TorrentField::FileStats.tvf_id(), &self.file_stats);
+        put(&mut tvf, TorrentField::Group.tvf_id(), &self.group);
+        put(
+            &mut tvf,
+            TorrentField::HashString.tvf_id(),
+            &self.hash_string,
+        );
+        put(
+            &mut tvf,
+            TorrentField::HaveUnchecked.tvf_id(),
+            &self.have_unchecked,
+        );
+        put(&mut tvf, TorrentField::HaveValid.tvf_id(), &self.have_valid);
+        self.honors_session_limits
+            .put(&mut tvf, TorrentField::HonorsSessionLimits.tvf_id());
+        put(&mut tvf, TorrentField::Id.tvf_id(), &self.id);
+        self.is_finished
+            .put(&mut tvf, TorrentField::IsFinished.tvf_id());
+        self.is_private
+            .put(&mut tvf, TorrentField::IsPrivate.tvf_id());
+        self.is_stalled
+            .put(&mut tvf, TorrentField::IsStalled.tvf_id());
+        put(&mut tvf, TorrentField::Labels.tvf_id(), &self.labels);
+        put(
+            &mut tvf,
+            TorrentField::LeftUntilDone.tvf_id(),
+            &self.left_until_done,
+        );
+        put(
+            &mut tvf,
+            TorrentField::MagnetLink.tvf_id(),
+            &self.magnet_link,
+        );
+        put(
+            &mut tvf,
+            TORRENT_MANUAL_ANNOUNCE_TIME,
+            &self.manual_announce_time,
+        );
+        put(
+            &mut tvf,
+            TorrentField::MaxConnectedPeers.tvf_id(),
+            &self.max_connected_peers,
+        );
+        put(
+            &mut tvf,
+            TorrentField::MetadataPercentComplete.tvf_id(),
+            &self.metadata_percent_complete,
+        );
+        put(&mut tvf, TorrentField::Name.tvf_id(), &self.name);
+        put(&mut tvf, TorrentField::PeerLimit.tvf_id(), &self.peer_limit);
+        put(&mut tvf, TorrentField::Peers.tvf_id(), &self.peers);
+        put(
+            &mut tvf,
+            TorrentField::PeersConnected.tvf_id(),
+            &self.peers_connected,
+        );
+        put(&mut tvf, TorrentField::PeersFrom.tvf_id(), &self.peers_from);
+        put(
+            &mut tvf,
+            TorrentField::PeersGettingFromUs.tvf_id(),
+            &self.peers_getting_from_us,
+        );
+        put(
+            &mut tvf,
+            TorrentField::PeersSendingToUs.tvf_id(),
+            &self.peers_sending_to_us,
+        );
+        put(
+            &mut tvf,
+            TorrentField::PercentComplete.tvf_id(),
+            &self.percent_complete,
+        );
+        put(
+            &mut tvf,
+            TorrentField::PercentDone.tvf_id(),
+            &self.percent_done,
+        );
+        if let Some(pieces) = &self.pieces {
+            tvf.put_bytes(
+                TorrentField::Pieces.tvf_id(),
+                Bytes::copy_from_slice(pieces),
+            );
+        }
+        put(
+            &mut tvf,
+            TorrentField::PieceCount.tvf_id(),
+            &self.piece_count,
+        );
+        put(&mut tvf, TorrentField::PieceSize.tvf_id(), &self.piece_size);
+        put(
+            &mut tvf,
+            TorrentField::Priorities.tvf_id(),
+            &self
+                .priorities
+                .as_ref()
+                .map(|p| p.iter().map(|e| *e as i64).collect::<Vec<_>>()),
+        );
+        put(
+            &mut tvf,
+            TorrentField::PrimaryMimeType.tvf_id(),
+            &self.primary_mime_type,
+        );
+        put(
+            &mut tvf,
+            TorrentField::QueuePosition.tvf_id(),
+            &self.queue_position,
+        );
+        put(
+            &mut tvf,
+            TorrentField::RateDownload.tvf_id(),
+            &self.rate_download,
+        );
+        put(
+            &mut tvf,
+            TorrentField::RateUpload.tvf_id(),
+            &self.rate_upload,
+        );
+        put(
+            &mut tvf,
+            TorrentField::RecheckProgress.tvf_id(),
+            &self.recheck_progress,
+        );
+        put(
+            &mut tvf,
+            TorrentField::SecondsDownloading.tvf_id(),
+            &self.seconds_downloading,
+        );
+        put(
+            &mut tvf,
+            TorrentField::SecondsSeeding.tvf_id(),
+            &self.seconds_seeding,
+        );
+        put(
+            &mut tvf,
+            TorrentField::SeedIdleLimit.tvf_id(),
+            &self.seed_idle_limit,
+        );
+        put(
+            &mut tvf,
+            TorrentField::SeedIdleMode.tvf_id(),
+            &self.seed_idle_mode.map(|e| e as i64),
+        );
+        put(
+            &mut tvf,
+            TorrentField::SeedRatioLimit.tvf_id(),
+            &self.seed_ratio_limit,
+        );
+        put(
+            &mut tvf,
+            TorrentField::SeedRatioMode.tvf_id(),
+            &self.seed_ratio_mode.map(|e| e as i64),
+        );
+        self.sequential_download
+            .put(&mut tvf, TorrentField::SequentialDownload.tvf_id());
+        put(
+            &mut tvf,
+            TorrentField::SequentialDownloadFromPiece.tvf_id(),
+            &self.sequential_download_from_piece,
+        );
+        put(
+            &mut tvf,
+            TorrentField::SizeWhenDone.tvf_id(),
+            &self.size_when_done,
+        );
+        put(&mut tvf, TorrentField::StartDate.tvf_id(), &self.start_date);
+        put(
+            &mut tvf,
+            TorrentField::Status.tvf_id(),
+            &self.status.map(|e| e as i64),
+        );
+        put(&mut tvf, TorrentField::Trackers.tvf_id(), &self.trackers);
+        put(
+            &mut tvf,
+            TorrentField::TrackerList.tvf_id(),
+            &self.tracker_list,
+        );
+        put(
+            &mut tvf,
+            TorrentField::TrackerStats.tvf_id(),
+            &self.tracker_stats,
+        );
+        put(&mut tvf, TorrentField::TotalSize.tvf_id(), &self.total_size);
+        put(
+            &mut tvf,
+            TorrentField::TorrentFile.tvf_id(),
+            &self.torrent_file,
+        );
+        put(
+            &mut tvf,
+            TorrentField::UploadedEver.tvf_id(),
+            &self.uploaded_ever,
+        );
+        put(
+            &mut tvf,
+            TorrentField::UploadLimit.tvf_id(),
+            &self.upload_limit,
+        );
+        self.upload_limited
+            .put(&mut tvf, TorrentField::UploadLimited.tvf_id());
+        put(
+            &mut tvf,
+            TorrentField::UploadRatio.tvf_id(),
+            &self.upload_ratio,
+        );
+        put(&mut tvf, TorrentField::Wanted.tvf_id(), &self.wanted);
+        put(&mut tvf, TorrentField::Webseeds.tvf_id(), &self.webseeds);
+        put(
+            &mut tvf,
+            TorrentField::WebseedsSendingToUs.tvf_id(),
+            &self.webseeds_sending_to_us,
+        );
+        tvf
+    }
+}
+
+impl FromTvf for TorrentsArguments {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(TorrentsArguments {
+            activity_date: get(tvf, TorrentField::ActivityDate.tvf_id()),
+            added_date: get(tvf, TorrentField::AddedDate.tvf_id()),
+            availability: get(tvf, TorrentField::Availability.tvf_id()),
+            bandwidth_priority: get_repr(tvf, TorrentField::BandwidthPriority.tvf_id()),
+            bytes_completed: get(tvf, TorrentField::BytesCompleted.tvf_id()),
+            comment: get(tvf, TorrentField::Comment.tvf_id()),
+            corrupt_ever: get(tvf, TorrentField::CorruptEver.tvf_id()),
+            creator: get(tvf, TorrentField::Creator.tvf_id()),
+            date_created: get(tvf, TorrentField::DateCreated.tvf_id()),
+            desired_available: get(tvf, TorrentField::DesiredAvailable.tvf_id()),
+            done_date: get(tvf, TorrentField::DoneDate.tvf_id()),
+            download_dir: get(tvf, TorrentField::DownloadDir.tvf_id()),
+            downloaded_ever: get(tvf, TorrentField::DownloadedEver.tvf_id()),
+            download_limit: get(tvf, TorrentField::DownloadLimit.tvf_id()),
+            download_limited: get(tvf, TorrentField::DownloadLimited.tvf_id()).unwrap_or_default(),
+            edit_date: get(tvf, TorrentField::EditDate.tvf_id()),
+            error: get_repr(tvf, TorrentField::Error.tvf_id()),
+            error_string: get(tvf, TorrentField::ErrorString.tvf_id()),
+            eta: get(tvf, TorrentField::Eta.tvf_id()),
+            eta_idle: get(tvf, TorrentField::EtaIdle.tvf_id()),
+            file_count: get(tvf, TorrentField::FileCount.tvf_id()),
+            files: get(tvf, TorrentField::Files.tvf_id()),
+            file_stats: get(tvf, TorrentField::FileStats.tvf_id()),
+            group: get(tvf, TorrentField::Group.tvf_id()),
+            hash_string: get(tvf, TorrentField::HashString.tvf_id()),
+            have_unchecked: get(tvf, TorrentField::HaveUnchecked.tvf_id()),
+            have_valid: get(tvf, TorrentField::HaveValid.tvf_id()),
+            honors_session_limits: get(tvf, TorrentField::HonorsSessionLimits.tvf_id())
+                .unwrap_or_default(),
+            id: get(tvf, TorrentField::Id.tvf_id()),
+            is_finished: get(tvf, TorrentField::IsFinished.tvf_id()).unwrap_or_default(),
+            is_private: get(tvf, TorrentField::IsPrivate.tvf_id()).unwrap_or_default(),
+            is_stalled: get(tvf, TorrentField::IsStalled.tvf_id()).unwrap_or_default(),
+            labels: get(tvf, TorrentField::Labels.tvf_id()),
+            left_until_done: get(tvf, TorrentField::LeftUntilDone.tvf_id()),
+            magnet_link: get(tvf, TorrentField::MagnetLink.tvf_id()),
+            manual_announce_time: get(tvf, TORRENT_MANUAL_ANNOUNCE_TIME),
+            max_connected_peers: get(tvf, TorrentField::MaxConnectedPeers.tvf_id()),
+            metadata_percent_complete: get(tvf, TorrentField::MetadataPercentComplete.tvf_id()),
+            name: get(tvf, TorrentField::Name.tvf_id()),
+            peer_limit: get(tvf, TorrentField::PeerLimit.tvf_id()),
+            peers: get(tvf, TorrentField::Peers.tvf_id()),
+            peers_connected: get(tvf, TorrentField::PeersConnected.tvf_id()),
+            peers_from: get(tvf, TorrentField::PeersFrom.tvf_id()),
+            peers_getting_from_us: get(tvf, TorrentField::PeersGettingFromUs.tvf_id()),
+            peers_sending_to_us: get(tvf, TorrentField::PeersSendingToUs.tvf_id()),
+            percent_complete: get(tvf, TorrentField::PercentComplete.tvf_id()),
+            percent_done: get(tvf, TorrentField::PercentDone.tvf_id()),
+            pieces: tvf
+                .get_bytes(TorrentField::Pieces.tvf_id())
+                .ok()
+                .map(|b| b.to_vec()),
+            piece_count: get(tvf, TorrentField::PieceCount.tvf_id()),
+            piece_size: get(tvf, TorrentField::PieceSize.tvf_id()),
+            priorities: get::<M, Vec<i64>>(tvf, TorrentField::Priorities.tvf_id())
+                .map(|p| p.into_iter().filter_map(from_repr).collect()),
+            primary_mime_type: get(tvf, TorrentField::PrimaryMimeType.tvf_id()),
+            queue_position: get(tvf, TorrentField::QueuePosition.tvf_id()),
+            rate_download: get(tvf, TorrentField::RateDownload.tvf_id()),
+            rate_upload: get(tvf, TorrentField::RateUpload.tvf_id()),
+            recheck_progress: get(tvf, TorrentField::RecheckProgress.tvf_id()),
+            seconds_downloading: get(tvf, TorrentField::SecondsDownloading.tvf_id()),
+            seconds_seeding: get(tvf, TorrentField::SecondsSeeding.tvf_id()),
+            seed_idle_limit: get(tvf, TorrentField::SeedIdleLimit.tvf_id()),
+            seed_idle_mode: get_repr(tvf, TorrentField::SeedIdleMode.tvf_id()),
+            seed_ratio_limit: get(tvf, TorrentField::SeedRatioLimit.tvf_id()),
+            seed_ratio_mode: get_repr(tvf, TorrentField::SeedRatioMode.tvf_id()),
+            sequential_download: get(tvf, TorrentField::SequentialDownload.tvf_id())
+                .unwrap_or_default(),
+            sequential_download_from_piece: get(
+                tvf,
+                TorrentField::SequentialDownloadFromPiece.tvf_id(),
+            ),
+            size_when_done: get(tvf, TorrentField::SizeWhenDone.tvf_id()),
+            start_date: get(tvf, TorrentField::StartDate.tvf_id()),
+            status: get_repr(tvf, TorrentField::Status.tvf_id()),
+            trackers: get(tvf, TorrentField::Trackers.tvf_id()),
+            tracker_list: get(tvf, TorrentField::TrackerList.tvf_id()),
+            tracker_stats: get(tvf, TorrentField::TrackerStats.tvf_id()),
+            total_size: get(tvf, TorrentField::TotalSize.tvf_id()),
+            torrent_file: get(tvf, TorrentField::TorrentFile.tvf_id()),
+            uploaded_ever: get(tvf, TorrentField::UploadedEver.tvf_id()),
+            upload_limit: get(tvf, TorrentField::UploadLimit.tvf_id()),
+            upload_limited: get(tvf, TorrentField::UploadLimited.tvf_id()).unwrap_or_default(),
+            upload_ratio: get(tvf, TorrentField::UploadRatio.tvf_id()),
+            wanted: get(tvf, TorrentField::Wanted.tvf_id()),
+            webseeds: get(tvf, TorrentField::Webseeds.tvf_id()),
+            webseeds_sending_to_us: get(tvf, TorrentField::WebseedsSendingToUs.tvf_id()),
+        })
+    }
+}
+impl ToTvf for File {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        self.bytes_completed.put(&mut tvf, FILE_BYTES_COMPLETED);
+        self.length.put(&mut tvf, FILE_LENGTH);
+        self.name.put(&mut tvf, FILE_NAME);
+        put(&mut tvf, FILE_BEGIN_PIECE, &self.begin_piece);
+        put(&mut tvf, FILE_END_PIECE, &self.end_piece);
+        tvf
+    }
+}
+
+impl FromTvf for File {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(File {
+            bytes_completed: get(tvf, FILE_BYTES_COMPLETED)?,
+            length: get(tvf, FILE_LENGTH)?,
+            name: get(tvf, FILE_NAME)?,
+            begin_piece: get(tvf, FILE_BEGIN_PIECE),
+            end_piece: get(tvf, FILE_END_PIECE),
+        })
+    }
+}
+
+impl ToTvf for FileStat {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        self.bytes_completed
+            .put(&mut tvf, FILE_STAT_BYTES_COMPLETED);
+        self.wanted.put(&mut tvf, FILE_STAT_WANTED);
+        (self.priority as i64).put(&mut tvf, FILE_STAT_PRIORITY);
+        tvf
+    }
+}
+
+impl FromTvf for FileStat {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(FileStat {
+            bytes_completed: get(tvf, FILE_STAT_BYTES_COMPLETED)?,
+            wanted: get(tvf, FILE_STAT_WANTED)?,
+            priority: get_repr(tvf, FILE_STAT_PRIORITY)?,
+        })
+    }
+}
+
+impl ToTvf for Peer {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        self.address.put(&mut tvf, PEER_ADDRESS);
+        self.bytes_to_client.put(&mut tvf, PEER_BYTES_TO_CLIENT);
+        self.bytes_to_peer.put(&mut tvf, PEER_BYTES_TO_PEER);
+        self.client_name.put(&mut tvf, PEER_CLIENT_NAME);
+        self.client_is_choked.put(&mut tvf, PEER_CLIENT_IS_CHOKED);
+        self.client_is_interested
+            .put(&mut tvf, PEER_CLIENT_IS_INTERESTED);
+        self.flag_str.put(&mut tvf, PEER_FLAG_STR);
+        self.is_downloading_from
+            .put(&mut tvf, PEER_IS_DOWNLOADING_FROM);
+        self.is_encrypted.put(&mut tvf, PEER_IS_ENCRYPTED);
+        self.is_incoming.put(&mut tvf, PEER_IS_INCOMING);
+        self.is_uploading_to.put(&mut tvf, PEER_IS_UPLOADING_TO);
+        self.is_utp.put(&mut tvf, PEER_IS_UTP);
+        self.peer_is_choked.put(&mut tvf, PEER_PEER_IS_CHOKED);
+        self.peer_is_interested
+            .put(&mut tvf, PEER_PEER_IS_INTERESTED);
+        self.peer_id.put(&mut tvf, PEER_PEER_ID);
+        self.port.put(&mut tvf, PEER_PORT);
+        self.progress.put(&mut tvf, PEER_PROGRESS);
+        self.rate_to_client.put(&mut tvf, PEER_RATE_TO_CLIENT);
+        self.rate_to_peer.put(&mut tvf, PEER_RATE_TO_PEER);
+        tvf
+    }
+}
+
+impl FromTvf for Peer {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(Peer {
+            address: get(tvf, PEER_ADDRESS)?,
+            bytes_to_client: get(tvf, PEER_BYTES_TO_CLIENT)?,
+            bytes_to_peer: get(tvf, PEER_BYTES_TO_PEER)?,
+            client_name: get(tvf, PEER_CLIENT_NAME)?,
+            client_is_choked: get(tvf, PEER_CLIENT_IS_CHOKED)?,
+            client_is_interested: get(tvf, PEER_CLIENT_IS_INTERESTED)?,
+            flag_str: get(tvf, PEER_FLAG_STR)?,
+            is_downloading_from: get(tvf, PEER_IS_DOWNLOADING_FROM)?,
+            is_encrypted: get(tvf, PEER_IS_ENCRYPTED)?,
+            is_incoming: get(tvf, PEER_IS_INCOMING)?,
+            is_uploading_to: get(tvf, PEER_IS_UPLOADING_TO)?,
+            is_utp: get(tvf, PEER_IS_UTP)?,
+            peer_is_choked: get(tvf, PEER_PEER_IS_CHOKED)?,
+            peer_is_interested: get(tvf, PEER_PEER_IS_INTERESTED)?,
+            peer_id: get(tvf, PEER_PEER_ID)?,
+            port: get(tvf, PEER_PORT)?,
+            progress: get(tvf, PEER_PROGRESS)?,
+            rate_to_client: get(tvf, PEER_RATE_TO_CLIENT)?,
+            rate_to_peer: get(tvf, PEER_RATE_TO_PEER)?,
+        })
+    }
+}
+
+impl ToTvf for PeerFrom {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        self.from_cache.put(&mut tvf, PEER_FROM_FROM_CACHE);
+        self.from_dht.put(&mut tvf, PEER_FROM_FROM_DHT);
+        self.from_incoming.put(&mut tvf, PEER_FROM_FROM_INCOMING);
+        self.from_lpd.put(&mut tvf, PEER_FROM_FROM_LPD);
+        self.from_ltep.put(&mut tvf, PEER_FROM_FROM_LTEP);
+        self.from_pex.put(&mut tvf, PEER_FROM_FROM_PEX);
+        self.from_tracker.put(&mut tvf, PEER_FROM_FROM_TRACKER);
+        tvf
+    }
+}
+
+impl FromTvf for PeerFrom {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(PeerFrom {
+            from_cache: get(tvf, PEER_FROM_FROM_CACHE)?,
+            from_dht: get(tvf, PEER_FROM_FROM_DHT)?,
+            from_incoming: get(tvf, PEER_FROM_FROM_INCOMING)?,
+            from_lpd: get(tvf, PEER_FROM_FROM_LPD)?,
+            from_ltep: get(tvf, PEER_FROM_FROM_LTEP)?,
+            from_pex: get(tvf, PEER_FROM_FROM_PEX)?,
+            from_tracker: get(tvf, PEER_FROM_FROM_TRACKER)?,
+        })
+    }
+}
+
+impl ToTvf for Tracker {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        self.announce.put(&mut tvf, TRACKER_ANNOUNCE);
+        self.id.put(&mut tvf, TRACKER_ID);
+        self.scrape.put(&mut tvf, TRACKER_SCRAPE);
+        self.sitename.put(&mut tvf, TRACKER_SITENAME);
+        self.tier.put(&mut tvf, TRACKER_TIER);
+        tvf
+    }
+}
+
+impl FromTvf for Tracker {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(Tracker {
+            announce: get(tvf, TRACKER_ANNOUNCE)?,
+            id: get(tvf, TRACKER_ID)?,
+            scrape: get(tvf, TRACKER_SCRAPE)?,
+            sitename: get(tvf, TRACKER_SITENAME)?,
+            tier: get(tvf, TRACKER_TIER)?,
+        })
+    }
+}
+
+impl ToTvf for TrackerStat {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        self.announce.put(&mut tvf, TRACKER_STAT_ANNOUNCE);
+        (self.announce_state as i64).put(&mut tvf, TRACKER_STAT_ANNOUNCE_STATE);
+        self.download_count
+            .put(&mut tvf, TRACKER_STAT_DOWNLOAD_COUNT);
+        self.downloader_count
+            .put(&mut tvf, TRACKER_STAT_DOWNLOADER_COUNT);
+        self.has_announced.put(&mut tvf, TRACKER_STAT_HAS_ANNOUNCED);
+        self.has_scraped.put(&mut tvf, TRACKER_STAT_HAS_SCRAPED);
+        self.host.put(&mut tvf, TRACKER_STAT_HOST);
+        self.id.put(&mut tvf, TRACKER_STAT_ID);
+        self.is_backup.put(&mut tvf, TRACKER_STAT_IS_BACKUP);
+        put(
+            &mut tvf,
+            TRACKER_STAT_LAST_ANNOUNCE_PEER_COUNT,
+            &self.last_announce_peer_count,
+        );
+        put(
+            &mut tvf,
+            TRACKER_STAT_LAST_ANNOUNCE_RESULT,
+            &self.last_announce_result,
+        );
+        put(
+            &mut tvf,
+            TRACKER_STAT_LAST_ANNOUNCE_START_TIME,
+            &self.last_announce_start_time,
+        );
+        self.last_announce_succeeded
+            .put(&mut tvf, TRACKER_STAT_LAST_ANNOUNCE_SUCCEEDED);
+        put(
+            &mut tvf,
+            TRACKER_STAT_LAST_ANNOUNCE_TIME,
+            &self.last_announce_time,
+        );
+        self.last_announce_timed_out
+            .put(&mut tvf, TRACKER_STAT_LAST_ANNOUNCE_TIMED_OUT);
+        put(
+            &mut tvf,
+            TRACKER_STAT_LAST_SCRAPE_RESULT,
+            &self.last_scrape_result,
+        );
+        put(
+            &mut tvf,
+            TRACKER_STAT_LAST_SCRAPE_START_TIME,
+            &self.last_scrape_start_time,
+        );
+        self.last_scrape_succeeded
+            .put(&mut tvf, TRACKER_STAT_LAST_SCRAPE_SUCCEEDED);
+        put(
+            &mut tvf,
+            TRACKER_STAT_LAST_SCRAPE_TIME,
+            &self.last_scrape_time,
+        );
+        self.last_scrape_timed_out
+            .put(&mut tvf, TRACKER_STAT_LAST_SCRAPE_TIMED_OUT);
+        self.leecher_count.put(&mut tvf, TRACKER_STAT_LEECHER_COUNT);
+        put(
+            &mut tvf,
+            TRACKER_STAT_NEXT_ANNOUNCE_TIME,
+            &self.next_announce_time,
+        );
+        put(
+            &mut tvf,
+            TRACKER_STAT_NEXT_SCRAPE_TIME,
+            &self.next_scrape_time,
+        );
+        self.scrape.put(&mut tvf, TRACKER_STAT_SCRAPE);
+        (self.scrape_state as i64).put(&mut tvf, TRACKER_STAT_SCRAPE_STATE);
+        self.seeder_count.put(&mut tvf, TRACKER_STAT_SEEDER_COUNT);
+        self.sitename.put(&mut tvf, TRACKER_STAT_SITENAME);
+        self.tier.put(&mut tvf, TRACKER_STAT_TIER);
+        tvf
+    }
+}
+
+impl FromTvf for TrackerStat {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(TrackerStat {
+            announce: get(tvf, TRACKER_STAT_ANNOUNCE)?,
+            announce_state: get_repr(tvf, TRACKER_STAT_ANNOUNCE_STATE)?,
+            download_count: get(tvf, TRACKER_STAT_DOWNLOAD_COUNT)?,
+            downloader_count: get(tvf, TRACKER_STAT_DOWNLOADER_COUNT)?,
+            has_announced: get(tvf, TRACKER_STAT_HAS_ANNOUNCED).unwrap_or_default(),
+            has_scraped: get(tvf, TRACKER_STAT_HAS_SCRAPED).unwrap_or_default(),
+            host: get(tvf, TRACKER_STAT_HOST)?,
+            id: get(tvf, TRACKER_STAT_ID)?,
+            is_backup: get(tvf, TRACKER_STAT_IS_BACKUP).unwrap_or_default(),
+            last_announce_peer_count: get(tvf, TRACKER_STAT_LAST_ANNOUNCE_PEER_COUNT),
+            last_announce_result: get(tvf, TRACKER_STAT_LAST_ANNOUNCE_RESULT),
+            last_announce_start_time: get(tvf, TRACKER_STAT_LAST_ANNOUNCE_START_TIME),
+            last_announce_succeeded: get(tvf, TRACKER_STAT_LAST_ANNOUNCE_SUCCEEDED)
+                .unwrap_or_default(),
+            last_announce_time: get(tvf, TRACKER_STAT_LAST_ANNOUNCE_TIME),
+            last_announce_timed_out: get(tvf, TRACKER_STAT_LAST_ANNOUNCE_TIMED_OUT)
+                .unwrap_or_default(),
+            last_scrape_result: get(tvf, TRACKER_STAT_LAST_SCRAPE_RESULT),
+            last_scrape_start_time: get(tvf, TRACKER_STAT_LAST_SCRAPE_START_TIME),
+            last_scrape_succeeded: get(tvf, TRACKER_STAT_LAST_SCRAPE_SUCCEEDED).unwrap_or_default(),
+            last_scrape_time: get(tvf, TRACKER_STAT_LAST_SCRAPE_TIME),
+            last_scrape_timed_out: get(tvf, TRACKER_STAT_LAST_SCRAPE_TIMED_OUT).unwrap_or_default(),
+            leecher_count: get(tvf, TRACKER_STAT_LEECHER_COUNT)?,
+            next_announce_time: get(tvf, TRACKER_STAT_NEXT_ANNOUNCE_TIME),
+            next_scrape_time: get(tvf, TRACKER_STAT_NEXT_SCRAPE_TIME),
+            scrape: get(tvf, TRACKER_STAT_SCRAPE)?,
+            scrape_state: get_repr(tvf, TRACKER_STAT_SCRAPE_STATE)?,
+            seeder_count: get(tvf, TRACKER_STAT_SEEDER_COUNT)?,
+            sitename: get(tvf, TRACKER_STAT_SITENAME)?,
+            tier: get(tvf, TRACKER_STAT_TIER)?,
+        })
+    }
+}
+
+impl ToTvf for SessionStats {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        self.uploaded_bytes
+            .put(&mut tvf, SESSION_STATS_UPLOADED_BYTES);
+        self.downloaded_bytes
+            .put(&mut tvf, SESSION_STATS_DOWNLOADED_BYTES);
+        self.files_added.put(&mut tvf, SESSION_STATS_FILES_ADDED);
+        put(&mut tvf, SESSION_STATS_SECONDS_ACTIVE, &self.seconds_active);
+        self.session_count
+            .put(&mut tvf, SESSION_STATS_SESSION_COUNT);
+        tvf
+    }
+}
+
+impl FromTvf for SessionStats {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(SessionStats {
+            uploaded_bytes: get(tvf, SESSION_STATS_UPLOADED_BYTES)?,
+            downloaded_bytes: get(tvf, SESSION_STATS_DOWNLOADED_BYTES)?,
+            files_added: get(tvf, SESSION_STATS_FILES_ADDED)?,
+            seconds_active: get(tvf, SESSION_STATS_SECONDS_ACTIVE),
+            session_count: get(tvf, SESSION_STATS_SESSION_COUNT)?,
+        })
+    }
+}
+
+impl ToTvf for TorrentSetParams {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        put(
+            &mut tvf,
+            TORRENT_SET_BANDWIDTH_PRIORITY,
+            &self.bandwidth_priority.map(|e| e as i64),
+        );
+        put(&mut tvf, TORRENT_SET_DOWNLOAD_LIMIT, &self.download_limit);
+        put(
+            &mut tvf,
+            TORRENT_SET_DOWNLOAD_LIMITED,
+            &self.download_limited,
+        );
+        put(&mut tvf, TORRENT_SET_FILES_UNWANTED, &self.files_unwanted);
+        put(&mut tvf, TORRENT_SET_FILES_WANTED, &self.files_wanted);
+        put(&mut tvf, TORRENT_SET_GROUP, &self.group);
+        put(
+            &mut tvf,
+            TORRENT_SET_HONORS_SESSION_LIMITS,
+            &self.honors_session_limits,
+        );
+        put(&mut tvf, TORRENT_SET_IDS, &self.ids);
+        put(&mut tvf, TORRENT_SET_LABELS, &self.labels);
+        put(&mut tvf, TORRENT_SET_LOCATION, &self.location);
+        put(&mut tvf, TORRENT_SET_PEER_LIMIT, &self.peer_limit);
+        put(&mut tvf, TORRENT_SET_PRIORITY_HIGH, &self.priority_high);
+        put(&mut tvf, TORRENT_SET_PRIORITY_LOW, &self.priority_low);
+        put(&mut tvf, TORRENT_SET_PRIORITY_NORMAL, &self.priority_normal);
+        put(&mut tvf, TORRENT_SET_QUEUE_POSITION, &self.queue_position);
+        put(&mut tvf, TORRENT_SET_SEED_IDLE_LIMIT, &self.seed_idle_limit);
+        put(
+            &mut tvf,
+            TORRENT_SET_SEED_IDLE_MODE,
+            &self.seed_idle_mode.map(|e| e as i64),
+        );
+        put(
+            &mut tvf,
+            TORRENT_SET_SEED_RATIO_LIMIT,
+            &self.seed_ratio_limit,
+        );
+        put(
+            &mut tvf,
+            TORRENT_SET_SEED_RATIO_MODE,
+            &self.seed_ratio_mode.map(|e| e as i64),
+        );
+        put(
+            &mut tvf,
+            TORRENT_SET_SEQUENTIAL_DOWNLOAD,
+            &self.sequential_download,
+        );
+        put(
+            &mut tvf,
+            TORRENT_SET_SEQUENTIAL_DOWNLOAD_FROM_PIECE,
+            &self.sequential_download_from_piece,
+        );
+        put(&mut tvf, TORRENT_SET_TRACKER_ADD, &self.tracker_add);
+        put(&mut tvf, TORRENT_SET_TRACKER_LIST, &self.tracker_list);
+        put(&mut tvf, TORRENT_SET_TRACKER_REMOVE, &self.tracker_remove);
+        put(&mut tvf, TORRENT_SET_TRACKER_REPLACE, &self.tracker_replace);
+        put(&mut tvf, TORRENT_SET_UPLOAD_LIMIT, &self.upload_limit);
+        put(&mut tvf, TORRENT_SET_UPLOAD_LIMITED, &self.upload_limited);
+        tvf
+    }
+}
+
+impl FromTvf for TorrentSetParams {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(TorrentSetParams {
+            bandwidth_priority: get_repr(tvf, TORRENT_SET_BANDWIDTH_PRIORITY),
+            download_limit: get(tvf, TORRENT_SET_DOWNLOAD_LIMIT),
+            download_limited: get(tvf, TORRENT_SET_DOWNLOAD_LIMITED),
+            files_unwanted: get(tvf, TORRENT_SET_FILES_UNWANTED),
+            files_wanted: get(tvf, TORRENT_SET_FILES_WANTED),
+            group: get(tvf, TORRENT_SET_GROUP),
+            honors_session_limits: get(tvf, TORRENT_SET_HONORS_SESSION_LIMITS),
+            ids: get(tvf, TORRENT_SET_IDS),
+            labels: get(tvf, TORRENT_SET_LABELS),
+            location: get(tvf, TORRENT_SET_LOCATION),
+            peer_limit: get(tvf, TORRENT_SET_PEER_LIMIT),
+            priority_high: get(tvf, TORRENT_SET_PRIORITY_HIGH),
+            priority_low: get(tvf, TORRENT_SET_PRIORITY_LOW),
+            priority_normal: get(tvf, TORRENT_SET_PRIORITY_NORMAL),
+            queue_position: get(tvf, TORRENT_SET_QUEUE_POSITION),
+            seed_idle_limit: get(tvf, TORRENT_SET_SEED_IDLE_LIMIT),
+            seed_idle_mode: get_repr(tvf, TORRENT_SET_SEED_IDLE_MODE),
+            seed_ratio_limit: get(tvf, TORRENT_SET_SEED_RATIO_LIMIT),
+            seed_ratio_mode: get_repr(tvf, TORRENT_SET_SEED_RATIO_MODE),
+            sequential_download: get(tvf, TORRENT_SET_SEQUENTIAL_DOWNLOAD),
+            sequential_download_from_piece: get(tvf, TORRENT_SET_SEQUENTIAL_DOWNLOAD_FROM_PIECE),
+            tracker_add: get(tvf, TORRENT_SET_TRACKER_ADD),
+            tracker_list: get(tvf, TORRENT_SET_TRACKER_LIST),
+            tracker_remove: get(tvf, TORRENT_SET_TRACKER_REMOVE),
+            tracker_replace: get(tvf, TORRENT_SET_TRACKER_REPLACE),
+            upload_limit: get(tvf, TORRENT_SET_UPLOAD_LIMIT),
+            upload_limited: get(tvf, TORRENT_SET_UPLOAD_LIMITED),
+        })
+    }
+}
+
+impl ToTvf for TorrentAddParams {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        put(&mut tvf, TORRENT_ADD_DOWNLOAD_DIR, &self.download_dir);
+        put(&mut tvf, TORRENT_ADD_FILENAME, &self.filename);
+        put(&mut tvf, TORRENT_ADD_LABELS, &self.labels);
+        put(&mut tvf, TORRENT_ADD_METAINFO, &self.metainfo);
+        put(&mut tvf, TORRENT_ADD_PAUSED, &self.paused);
+        tvf
+    }
+}
+
+impl FromTvf for TorrentAddParams {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        Some(TorrentAddParams {
+            download_dir: get(tvf, TORRENT_ADD_DOWNLOAD_DIR),
+            filename: get(tvf, TORRENT_ADD_FILENAME),
+            labels: get(tvf, TORRENT_ADD_LABELS),
+            metainfo: get(tvf, TORRENT_ADD_METAINFO),
+            paused: get(tvf, TORRENT_ADD_PAUSED),
+        })
+    }
+}
+
+impl ToTvf for TorrentId {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        match self {
+            TorrentId::RecentlyActive => true.put(&mut tvf, TORRENT_ID_RECENTLY_ACTIVE),
+            TorrentId::Id(id) => id.put(&mut tvf, TORRENT_ID_ID),
+            TorrentId::List(ids) => ids.put(&mut tvf, TORRENT_ID_LIST),
+        }
+        tvf
+    }
+}
+
+impl FromTvf for TorrentId {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        if get::<M, bool>(tvf, TORRENT_ID_RECENTLY_ACTIVE).unwrap_or_default() {
+            Some(TorrentId::RecentlyActive)
+        } else if let Some(id) = get(tvf, TORRENT_ID_ID) {
+            Some(TorrentId::Id(id))
+        } else {
+            get(tvf, TORRENT_ID_LIST).map(TorrentId::List)
+        }
+    }
+}
+
+impl ToTvf for Method {
+    fn to_tvf<M>(&self) -> M
+    where
+        M: Tvf + Default,
+    {
+        let mut tvf = M::default();
+        let name = match self {
+            Method::TorrentStart(ids) => {
+                put(&mut tvf, METHOD_IDS, ids);
+                "torrent-start"
+            }
+            Method::TorrentStartNow(ids) => {
+                put(&mut tvf, METHOD_IDS, ids);
+                "torrent-start-now"
+            }
+            Method::TorrentStop(ids) => {
+                put(&mut tvf, METHOD_IDS, ids);
+                "torrent-stop"
+            }
+            Method::TorrentVerify(ids) => {
+                put(&mut tvf, METHOD_IDS, ids);
+                "torrent-verify"
+            }
+            Method::TorrentReannounce(ids) => {
+                put(&mut tvf, METHOD_IDS, ids);
+                "torrent-reannounce"
+            }
+            Method::TorrentSet(params) => {
+                params.as_ref().put(&mut tvf, METHOD_ARGUMENTS);
+                "torrent-set"
+            }
+            Method::TorrentGet(fields, ids, format) => {
+                fields
+                    .iter()
+                    .filter_map(|f| serde_json::to_value(f).ok()?.as_str().map(String::from))
+                    .collect::<Vec<_>>()
+                    .put(&mut tvf, METHOD_FIELDS);
+                put(&mut tvf, METHOD_IDS, ids);
+                if *format == Some(TorrentGetFormat::Table) {
+                    true.put(&mut tvf, METHOD_TABLE);
+                }
+                "torrent-get"
+            }
+            Method::TorrentRemove(ids, delete_local_data) => {
+                ids.put(&mut tvf, METHOD_IDS);
+                delete_local_data.put(&mut tvf, METHOD_DELETE_LOCAL_DATA);
+                "torrent-remove"
+            }
+            Method::TorrentAdd(params) => {
+                params.as_ref().put(&mut tvf, METHOD_ARGUMENTS);
+                "torrent-add"
+            }
+            Method::SessionStats => "session-stats",
+            Method::SessionGet => "session-get",
+        };
+        tvf.put_string(METHOD_NAME, name);
+        tvf
+    }
+}
+
+impl FromTvf for Method {
+    fn from_tvf<M>(tvf: &M) -> Option<Self>
+    where
+        M: Tvf + Clone,
+    {
+        match tvf.get_string(METHOD_NAME).ok()?.as_str() {
+            "torrent-start" => Some(Method::TorrentStart(get(tvf, METHOD_IDS))),
+            "torrent-start-now" => Some(Method::TorrentStartNow(get(tvf, METHOD_IDS))),
+            "torrent-stop" => Some(Method::TorrentStop(get(tvf, METHOD_IDS))),
+            "torrent-verify" => Some(Method::TorrentVerify(get(tvf, METHOD_IDS))),
+            "torrent-reannounce" => Some(Method::TorrentReannounce(get(tvf, METHOD_IDS))),
+            "torrent-set" => Some(Method::TorrentSet(Box::new(get(tvf, METHOD_ARGUMENTS)?))),
+            "torrent-get" => {
+                let fields = get::<M, Vec<String>>(tvf, METHOD_FIELDS)?
+                    .into_iter()
+                    .map(|f| serde_json::from_value(serde_json::Value::String(f)).ok())
+                    .collect::<Option<Vec<TorrentField>>>()?;
+                let format = get::<M, bool>(tvf, METHOD_TABLE)
+                    .unwrap_or_default()
+                    .then_some(TorrentGetFormat::Table);
+                Some(Method::TorrentGet(fields, get(tvf, METHOD_IDS), format))
+            }
+            "torrent-remove" => Some(Method::TorrentRemove(
+                get(tvf, METHOD_IDS)?,
+                get(tvf, METHOD_DELETE_LOCAL_DATA).unwrap_or_default(),
+            )),
+            "torrent-add" => Some(Method::TorrentAdd(Box::new(get(tvf, METHOD_ARGUMENTS)?))),
+            "session-stats" => Some(Method::SessionStats),
+            "session-get" => Some(Method::SessionGet),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prosa_utils::msg::simple_string_tvf::SimpleStringTvf;
+
+    use super::*;
+
+    #[test]
+    fn tvf_round_trip() {
+        let torrent: TorrentsArguments = serde_json::from_str(
+            r#"{ "id": 1, "hashString": "abc", "name": "Movie", "status": 6, "addedDate": 1700000000, "secondsSeeding": 3600,
+                "error": 3, "isPrivate": true, "labels": ["tv", "hd"], "percentDone": 0.25, "pieces": "wA==", "priorities": [-1, 0, 1],
+                "bandwidthPriority": 1, "seedRatioMode": 2, "wanted": [true, false], "rateUpload": -1,
+                "peersFrom": { "fromCache": 0, "fromDht": 1, "fromIncoming": 2, "fromLpd": 3, "fromLtep": 4, "fromPex": 5, "fromTracker": 6 },
+                "files": [ { "bytesCompleted": 10, "length": 4, "name": "Movie/a.mkv", "begin_piece": 0, "end_piece": 1 } ],
+                "trackerStats": [ { "announce": "a", "announceState": 1, "downloadCount": 0, "downloader_count": 0, "host": "a", "id": 0, "leecherCount": 10,
+                    "scrape": "", "scrapeState": 3, "seederCount": 4, "sitename": "a", "tier": 0, "lastAnnounceTime": 1700000100, "hasAnnounced": true } ] }"#,
+        )
+        .unwrap();
+        let tvf: SimpleStringTvf = torrent.to_tvf();
+        assert_eq!(
+            "Movie",
+            tvf.get_string(TorrentField::Name.tvf_id())
+                .unwrap()
+                .as_str()
+        );
+        let decoded = TorrentsArguments::from_tvf(&tvf).unwrap();
+        assert_eq!(format!("{torrent:?}"), format!("{decoded:?}"));
+
+        let stats = SessionStats {
+            uploaded_bytes: 10,
+            downloaded_bytes: 20,
+            files_added: 1,
+            seconds_active: TimeDelta::try_seconds(60),
+            session_count: 2,
+        };
+        let tvf: SimpleStringTvf = stats.to_tvf();
+        assert_eq!(
+            format!("{stats:?}"),
+            format!("{:?}", SessionStats::from_tvf(&tvf).unwrap())
+        );
+
+        let methods = [
+            Method::TorrentStart(Some(TorrentId::RecentlyActive)),
+            Method::TorrentStop(Some(TorrentId::List(vec![
+                Id::Id(1),
+                Id::Hash("abc".to_string()),
+            ]))),
+            Method::TorrentGet(
+                vec![TorrentField::Id, TorrentField::FileCount],
+                Some(vec![TorrentId::Id(Id::Id(2))]),
+                Some(TorrentGetFormat::Table),
+            ),
+            Method::TorrentRemove(vec![TorrentId::Id(Id::Id(3))], true),
+            Method::TorrentSet(Box::new(TorrentSetParams {
+                ids: Some(vec![TorrentId::Id(Id::Id(4))]),
+                labels: Some(vec!["tv".to_string()]),
+                seed_ratio_limit: Some(1.5),
+                bandwidth_priority: Some(crate::transmission::api::Priority::Low),
+                ..Default::default()
+            })),
+            Method::SessionStats,
+        ];
+        for method in methods {
+            let tvf: SimpleStringTvf = method.to_tvf();
+            assert_eq!(
+                serde_json::to_string(&method).unwrap(),
+                serde_json::to_string(&Method::from_tvf(&tvf).unwrap()).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as base64};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::transmission::api::{Method, Response, TorrentAddParams};
+
+/// Subfolder where files added to Transmission are moved
+const DONE_FOLDER: &str = "done";
+
+/// Subfolder where files that can't be added are moved
+const FAILED_FOLDER: &str = "failed";
+
+/// Suffixes of the temporary files written by browsers and download tools
+const TEMPORARY_SUFFIXES: [&str; 5] = [".part", ".partial", ".crdownload", ".tmp", "~"];
+
+/// Check if a file is hidden or temporary, so it must not be added
+fn is_temporary(file_name: &str) -> bool {
+    let file_name = file_name.to_ascii_lowercase();
+    file_name.starts_with('.')
+        || TEMPORARY_SUFFIXES
+            .iter()
+            .any(|suffix| file_name.ends_with(suffix))
+}
+
+/// Settings of a watch folder
+#[derive(Debug, Deserialize)]
+pub struct WatchFolderSettings {
+    /// Directory scanned for `.torrent` files, and magnet links in `.magnet` or `.txt` files
+    pub path: PathBuf,
+    /// Download directory of the torrents added from this folder
+    pub download_dir: Option<String>,
+    /// Labels set on the torrents added from this folder
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Add the torrents without starting them
+    #[serde(default)]
+    pub paused: bool,
+    /// Amount of seconds a file must stay unmodified before it's added, to not read a file that is still written
+    #[serde(default = "WatchFolderSettings::default_settle_delay")]
+    pub settle_delay: u64,
+}
+
+impl WatchFolderSettings {
+    fn default_settle_delay() -> u64 {
+        5
+    }
+
+    /// Build the `torrent-add` parameters of a file of the folder.
+    /// Return `None` if the file is not a torrent or a magnet file
+    async fn add_params(&self, path: &Path) -> std::io::Result<Option<TorrentAddParams>> {
+        let mut params = TorrentAddParams {
+            download_dir: self.download_dir.clone(),
+            labels: (!self.labels.is_empty()).then(|| self.labels.clone()),
+            paused: self.paused.then_some(true),
+            ..Default::default()
+        };
+
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("torrent") => {
+                params.metainfo = Some(base64.encode(tokio::fs::read(path).await?));
+            }
+            Some("magnet") | Some("txt") => {
+                let content = tokio::fs::read_to_string(path).await?;
+                let magnet = content
+                    .lines()
+                    .map(str::trim)
+                    .find(|l| l.starts_with("magnet:"))
+                    .ok_or_else(|| {
+                        std::io::Error::new(std::io::ErrorKind::InvalidData, "no magnet link")
+                    })?;
+                params.filename = Some(magnet.to_string());
+            }
+            _ => return Ok(None),
+        }
+
+        Ok(Some(params))
+    }
+}
+
+/// Move a processed file of a watch folder in one of its subfolders
+async fn move_file(path: &Path, subfolder: &str) {
+    if let (Some(folder), Some(file_name)) = (path.parent(), path.file_name()) {
+        let target = folder.join(subfolder);
+        if let Err(e) = tokio::fs::create_dir_all(&target).await {
+            warn!("Can't create the watch folder {target:?}: {e}");
+        } else if let Err(e) = tokio::fs::rename(path, target.join(file_name)).await {
+            warn!("Can't move {path:?} to {target:?}: {e}");
+        }
+    }
+}
+
+/// State of the watch folders: files submitted with `torrent-add`, waiting for their result
+#[derive(Default, Debug)]
+pub struct WatchFolders {
+    /// Submitted files, in the order of their `torrent-add` methods
+    pending: VecDeque<PathBuf>,
+}
+
+impl WatchFolders {
+    /// Scan the watch folders, and return a `torrent-add` method for every file found.
+    /// Hidden, temporary and recently modified files are skipped. Files that can't be read are moved in the `failed` subfolder
+    pub async fn scan(&mut self, settings: &[WatchFolderSettings]) -> Vec<Method> {
+        // Files of a previous cycle still pending were not processed, so they'll be found again
+        self.pending.clear();
+
+        let mut methods = Vec::new();
+        for folder in settings {
+            let mut entries = match tokio::fs::read_dir(&folder.path).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("Can't scan the watch folder {:?}: {e}", folder.path);
+                    continue;
+                }
+            };
+
+            let settle_delay = Duration::from_secs(folder.settle_delay);
+            let mut paths = Vec::new();
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                if entry.file_name().to_str().is_none_or(is_temporary) {
+                    continue;
+                }
+
+                // Files still written are found again on a next scan. A modification date in the future is considered recent
+                if let Ok(metadata) = entry.metadata().await
+                    && metadata.is_file()
+                    && metadata
+                        .modified()
+                        .map(|m| m.elapsed().is_ok_and(|e| e >= settle_delay))
+                        .unwrap_or(true)
+                {
+                    paths.push(entry.path());
+                }
+            }
+            paths.sort();
+
+            for path in paths {
+                match folder.add_params(&path).await {
+                    Ok(Some(params)) => {
+                        methods.push(Method::TorrentAdd(Box::new(params)));
+                        self.pending.push_back(path);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!("Can't read the watched file {path:?}: {e}");
+                        move_file(&path, FAILED_FOLDER).await;
+                    }
+                }
+            }
+        }
+
+        methods
+    }
+
+    /// Process the response of the next `torrent-add` submitted from a watch folder, to move its file in the `done` or `failed` subfolder.
+    /// Return `false` if no file is waiting for a result
+    pub async fn process_response(&mut self, response: &Response) -> bool {
+        if let Some(path) = self.pending.pop_front() {
+            if !response.is_success() {
+                warn!("Can't add the torrent of {path:?}: {}", response.result);
+                move_file(&path, FAILED_FOLDER).await;
+            } else if let Some(duplicate) = &response.arguments.torrent_duplicate {
+                info!(
+                    "Torrent of {path:?} is already in Transmission: {}",
+                    duplicate.name.as_deref().unwrap_or_default()
+                );
+                move_file(&path, DONE_FOLDER).await;
+            } else {
+                info!(
+                    "Torrent of {path:?} added: {}",
+                    response
+                        .arguments
+                        .torrent_added
+                        .as_ref()
+                        .and_then(|t| t.name.as_deref())
+                        .unwrap_or_default()
+                );
+                move_file(&path, DONE_FOLDER).await;
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn watch_folder() {
+        let path =
+            std::env::temp_dir().join(format!("prosa-ovserver-watch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(path.join("a.torrent"), b"d4:infod4:name3:isoee").unwrap();
+        std::fs::write(
+            path.join("b.magnet"),
+            "\nmagnet:?xt=urn:btih:a2b5d9e8f1c3b4a5968778695a4b3c2d1e0f9a8b\n",
+        )
+        .unwrap();
+        std::fs::write(path.join("c.txt"), "not a magnet").unwrap();
+        std::fs::write(path.join("d.TORRENT"), b"d4:infod4:name3:txtee").unwrap();
+        std::fs::write(path.join("e.nfo"), "release notes").unwrap();
+        std::fs::write(path.join(".f.torrent"), b"d4:infod4:name3:isoee").unwrap();
+        std::fs::write(path.join("g.torrent.part"), b"d4:info").unwrap();
+        std::fs::write(path.join("h.magnet~"), "magnet:?xt=urn:btih:0").unwrap();
+
+        let settings = vec![WatchFolderSettings {
+            path: path.clone(),
+            download_dir: Some("/downloads/iso".to_string()),
+            labels: vec!["watch".to_string()],
+            paused: false,
+            settle_delay: 0,
+        }];
+        let mut watch_folders = WatchFolders::default();
+        let methods = watch_folders.scan(&settings).await;
+        assert_eq!(3, methods.len());
+        let Method::TorrentAdd(params) = &methods[0] else {
+            panic!("wrong method {:?}", methods[0]);
+        };
+        assert_eq!(
+            Some("ZDQ6aW5mb2Q0Om5hbWUzOmlzb2Vl"),
+            params.metainfo.as_deref()
+        );
+        assert_eq!(Some("/downloads/iso"), params.download_dir.as_deref());
+        assert_eq!(Some(vec!["watch".to_string()]), params.labels);
+        assert_eq!(None, params.paused);
+        let Method::TorrentAdd(params) = &methods[1] else {
+            panic!("wrong method {:?}", methods[1]);
+        };
+        assert_eq!(
+            Some("magnet:?xt=urn:btih:a2b5d9e8f1c3b4a5968778695a4b3c2d1e0f9a8b"),
+            params.filename.as_deref()
+        );
+        assert!(path.join("failed/c.txt").exists());
+        assert!(path.join("e.nfo").exists());
+        assert!(path.join(".f.torrent").exists());
+        assert!(path.join("g.torrent.part").exists());
+        assert!(path.join("h.magnet~").exists());
+
+        for response in [
+            r#"{"arguments":{"torrent-added":{"hashString":"0a1b","id":1,"name":"iso"}},"result":"success"}"#,
+            r#"{"arguments":{"torrent-duplicate":{"hashString":"a2b5","id":7,"name":"Fedora"}},"result":"success"}"#,
+            r#"{"arguments":{},"result":"invalid or corrupt torrent file"}"#,
+        ] {
+            watch_folders
+                .process_response(&serde_json::from_str(response).unwrap())
+                .await;
+        }
+        assert!(path.join("done/a.torrent").exists());
+        assert!(path.join("done/b.magnet").exists());
+        assert!(path.join("failed/d.TORRENT").exists());
+        assert!(watch_folders.scan(&settings).await.is_empty());
+
+        // A file just written is added once it's settled
+        std::fs::write(path.join("i.torrent"), b"d4:infod4:name3:isoee").unwrap();
+        let mut settled_settings = serde_json::from_value::<Vec<WatchFolderSettings>>(
+            serde_json::json!([{ "path": path }]),
+        )
+        .unwrap();
+        assert_eq!(5, settled_settings[0].settle_delay);
+        assert!(watch_folders.scan(&settled_settings).await.is_empty());
+        settled_settings[0].settle_delay = 0;
+        assert_eq!(1, watch_folders.scan(&settled_settings).await.len());
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}